@@ -0,0 +1,56 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use subleq_self_replicator::{next_generation, GaConfig, SUBLEQProgram};
+
+fn population(config: &GaConfig, rng: &mut impl Rng) -> Vec<SUBLEQProgram> {
+    (0..config.population)
+        .map(|_| {
+            let length = rng.gen_range(config.min_len..=config.max_len);
+            SUBLEQProgram::new(length, config.memory_size, rng)
+        })
+        .collect()
+}
+
+fn crossover_mutate(c: &mut Criterion) {
+    let config = GaConfig::builder().indel_rate(0.1).build().unwrap();
+    let mutation = config.mutation();
+    let mut rng = StdRng::seed_from_u64(1);
+    let parents = population(&GaConfig::builder().population(2).build().unwrap(), &mut rng);
+
+    c.bench_function("crossover+mutate", |b| {
+        b.iter(|| {
+            let mut child = config.crossover.apply(&parents[0], &parents[1], config.memory_size, &mut rng);
+            child.mutate(&mutation, &mut rng);
+            black_box(child)
+        })
+    });
+}
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    group.sample_size(10);
+    for parallel_breeding in [false, true] {
+        let config = GaConfig::builder().population(1000).parallel_breeding(parallel_breeding).build().unwrap();
+        let vm = config.vm();
+        let mut rng = StdRng::seed_from_u64(1);
+        let initial = population(&config, &mut rng);
+        let name = if parallel_breeding { "parallel-breeding" } else { "sequential-breeding" };
+
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || initial.clone(),
+                |population| {
+                    let scores: Vec<usize> = population.par_iter().map(|p| p.fitness(&vm, config.fitness)).collect();
+                    next_generation(&population, &scores, &config, &mut rng)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, crossover_mutate, generation);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use subleq_self_replicator::{FitnessMode, GaConfig, SUBLEQProgram};
+
+fn programs(count: usize, memory_size: i32) -> Vec<SUBLEQProgram> {
+    let mut rng = StdRng::seed_from_u64(1);
+    (0..count).map(|_| SUBLEQProgram::new(32, memory_size, &mut rng)).collect()
+}
+
+fn execute(c: &mut Criterion) {
+    let vm = GaConfig::default().vm();
+    let programs = programs(100, vm.memory_size);
+
+    c.bench_function("execute", |b| {
+        b.iter(|| {
+            for program in &programs {
+                black_box(program.execute(&vm));
+            }
+        })
+    });
+    c.bench_function("execute_into", |b| {
+        let mut memory = vec![0; vm.memory_size as usize];
+        b.iter(|| {
+            for program in &programs {
+                black_box(program.execute_into(&vm, &mut memory));
+            }
+        })
+    });
+}
+
+fn fitness(c: &mut Criterion) {
+    let vm = GaConfig::default().vm();
+    let programs = programs(100, vm.memory_size);
+
+    for (name, mode) in [("fitness/prefix", FitnessMode::Prefix), ("fitness/copies", FitnessMode::Copies)] {
+        c.bench_function(name, |b| {
+            b.iter(|| programs.iter().map(|program| program.fitness(&vm, mode)).sum::<usize>())
+        });
+    }
+}
+
+criterion_group!(benches, execute, fitness);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Runs arbitrary programs through every entry point that executes them, under
+//! arbitrary VM options, checking that none of them panics.
+//!
+//! Input layout: a flags byte, an I/O port byte and a memory size byte, then
+//! little-endian `i32` words. The first 64 words (the default maximum program
+//! length) are the program and any further words are its input.
+//!
+//! Run with the hand-made seeds alongside the generated corpus, which comes first
+//! so that new inputs are written there:
+//!
+//! ```text
+//! mkdir -p fuzz/corpus/execute
+//! cargo +nightly fuzz run execute fuzz/corpus/execute fuzz/seeds/execute
+//! ```
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use subleq_self_replicator::{FitnessMode, GaConfig, InstructionSet, SUBLEQProgram};
+
+const MAX_PROGRAM_LENGTH: usize = 64;
+
+fuzz_target!(|data: &[u8]| {
+    let [flags, port, memory, ref rest @ ..] = *data else {
+        return;
+    };
+    let memory_size = if flags & 8 != 0 { i32::from(memory).max(3) } else { 256 };
+    let mut words: Vec<i32> = rest.chunks_exact(4).map(|w| i32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+    let input = words.split_off(words.len().min(MAX_PROGRAM_LENGTH).min(memory_size as usize));
+
+    let mut builder = GaConfig::builder()
+        .memory_size(memory_size)
+        .min_len(1)
+        .max_len(memory_size as usize)
+        .halt_on_negative(flags & 1 != 0)
+        .detect_cycles(flags & 2 != 0)
+        .wrap_pc(flags & 64 != 0)
+        .protect_code(flags & 128 != 0)
+        .isa(match flags >> 4 & 3 {
+            1 => InstructionSet::Subneg,
+            2 => InstructionSet::Addleq,
+            _ => InstructionSet::Subleq,
+        })
+        .input(input);
+    if flags & 4 != 0 {
+        builder = builder.io_port(usize::from(port) % memory_size as usize);
+    }
+    let vm = builder.build().unwrap().vm();
+
+    let program = SUBLEQProgram::from_code(words);
+    let result = program.execute(&vm);
+    assert_eq!(result.memory.len(), memory_size as usize);
+    program.fitness(&vm, FitnessMode::Prefix);
+    program.fitness(&vm, FitnessMode::Copies);
+    program.verify_replication(&vm);
+});
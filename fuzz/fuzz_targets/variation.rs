@@ -0,0 +1,81 @@
+//! Mutates and recombines arbitrary valid programs under arbitrary length bounds,
+//! checking that no operator panics and that offspring stay valid: lengths within
+//! `min_len..=max_len` (and, for crossover, between the parents' lengths) and genes
+//! within `-memory_size..memory_size`.
+//!
+//! Input layout: a flags byte, a memory size byte, bytes choosing the length bounds,
+//! the two parents' lengths, the mutation rate and the indel rate, then an 8-byte
+//! little-endian seed for the random generator and little-endian `i32` words, which
+//! are wrapped into the gene range and cycled to fill both parents. Every input is
+//! replayed with the same random choices, so a failing one reproduces exactly.
+//!
+//! Run it like the `execute` target:
+//!
+//! ```text
+//! mkdir -p fuzz/corpus/variation
+//! cargo +nightly fuzz run variation fuzz/corpus/variation fuzz/seeds/variation
+//! ```
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use subleq_self_replicator::{Crossover, GaConfig, MutationOperator, SUBLEQProgram};
+
+fuzz_target!(|data: &[u8]| {
+    let [flags, memory, min_len, span, len_a, len_b, rate, indel, ref rest @ ..] = *data else {
+        return;
+    };
+    let Some((seed, words)) = rest.split_first_chunk::<8>() else {
+        return;
+    };
+    let memory_size = if flags & 8 != 0 { i32::from(memory).max(3) } else { 256 };
+    let min_len = 1 + usize::from(min_len) % memory_size as usize;
+    let max_len = (min_len + usize::from(span)).min(memory_size as usize);
+    let config = GaConfig::builder()
+        .memory_size(memory_size)
+        .min_len(min_len)
+        .max_len(max_len)
+        .mutation_rate(f64::from(rate) / 255.0)
+        .indel_rate(f64::from(indel) / 255.0)
+        .self_adaptation(if flags & 4 != 0 { 1.0 } else { 0.0 })
+        .mutation_operator(match flags >> 4 & 3 {
+            1 => MutationOperator::Block,
+            2 => MutationOperator::Both,
+            _ => MutationOperator::Point,
+        })
+        .block_rate(f64::from(rate) / 255.0)
+        .build()
+        .unwrap();
+    let mut rng = ChaCha12Rng::seed_from_u64(u64::from_le_bytes(*seed));
+
+    let mut genes = words.chunks_exact(4).map(|w| i32::from_le_bytes([w[0], w[1], w[2], w[3]]).rem_euclid(2 * memory_size) - memory_size).cycle();
+    let lengths = max_len - min_len + 1;
+    let mut parent = |len: u8| {
+        let code: Vec<i32> = (0..min_len + usize::from(len) % lengths).map(|_| genes.next().unwrap_or(0)).collect();
+        SUBLEQProgram::from_code(code).with_mutation_rate(config.mutation_rate)
+    };
+    let (a, b) = (parent(len_a), parent(len_b));
+    let valid = |program: &SUBLEQProgram| {
+        assert!((min_len..=max_len).contains(&program.code().len()), "length {} outside {}..={}", program.code().len(), min_len, max_len);
+        assert!(program.code().iter().all(|gene| (-memory_size..memory_size).contains(gene)), "gene out of range in {:?}", program.code());
+    };
+
+    let crossover = match flags & 3 {
+        1 => Crossover::TwoPoint,
+        2 => Crossover::Uniform,
+        _ => Crossover::Single,
+    };
+    let mut child = crossover.apply(&a, &b, memory_size, &mut rng);
+    let (shorter, longer) = (a.code().len().min(b.code().len()), a.code().len().max(b.code().len()));
+    assert!((shorter..=longer).contains(&child.code().len()), "child of {} words from parents of {} and {}", child.code().len(), shorter, longer);
+    valid(&child);
+
+    for _ in 0..8 {
+        let len = child.code().len();
+        child.mutate(&config.mutation(), &mut rng);
+        valid(&child);
+        // Only insertions and deletions change the length
+        assert!(config.indel_rate > 0.0 || child.code().len() == len, "mutation changed the length from {} to {}", len, child.code().len());
+    }
+});
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+
+use crate::MUTATION_RATE_RANGE;
+
+/// Generations of offspring pooled before the 1/5 success rule adjusts the rate.
+pub const ONE_FIFTH_WINDOW: usize = 5;
+
+/// Factor the 1/5 success rule divides the rate by after a successful window, and
+/// multiplies it by after an unsuccessful one.
+pub const ONE_FIFTH_FACTOR: f64 = 0.85;
+
+/// How the global mutation rate changes during a run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdaptiveMutation {
+    /// Keep `mutation_rate` fixed.
+    #[default]
+    Off,
+    /// Raise the rate while more than a fifth of the offspring beat their fitter parent,
+    /// lower it while fewer do.
+    OneFifth,
+}
+
+/// Rechenberg's 1/5 success rule applied to a factor on every program's mutation rate.
+pub(crate) struct OneFifthRule {
+    base_rate: f64,
+    scale: f64,
+    successes: usize,
+    trials: usize,
+    generations: usize,
+}
+
+impl OneFifthRule {
+    pub(crate) fn new(base_rate: f64) -> Self {
+        OneFifthRule { base_rate, scale: 1.0, successes: 0, trials: 0, generations: 0 }
+    }
+
+    /// Factor mutation rates are multiplied by.
+    pub(crate) fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The current global mutation rate.
+    pub(crate) fn rate(&self) -> f64 {
+        self.base_rate * self.scale
+    }
+
+    /// Records one generation's offspring, of which `successes` out of `trials` scored
+    /// higher than their fitter parent, and adjusts the rate at the end of each window.
+    ///
+    /// The rate stays within [`MUTATION_RATE_RANGE`]; a window without offspring, or
+    /// with exactly a fifth succeeding, leaves it unchanged.
+    pub(crate) fn record(&mut self, successes: usize, trials: usize) {
+        self.successes += successes;
+        self.trials += trials;
+        self.generations += 1;
+        if self.generations < ONE_FIFTH_WINDOW {
+            return;
+        }
+        if self.trials > 0 && self.base_rate > 0.0 {
+            let ratio = self.successes as f64 / self.trials as f64;
+            let rate = if ratio > 0.2 {
+                self.rate() / ONE_FIFTH_FACTOR
+            } else if ratio < 0.2 {
+                self.rate() * ONE_FIFTH_FACTOR
+            } else {
+                self.rate()
+            };
+            self.scale = rate.clamp(MUTATION_RATE_RANGE.0, MUTATION_RATE_RANGE.1) / self.base_rate;
+        }
+        (self.successes, self.trials, self.generations) = (0, 0, 0);
+    }
+}
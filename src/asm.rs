@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use crate::{InstructionSet, SUBLEQProgram};
+
+/// Renders `code` as one line per instruction, `SUBLEQ a, b, c` or the mnemonic of
+/// another `isa`, prefixed by its offset.
+///
+/// Operands are printed as stored, with the addresses they reduce to under
+/// `rem_euclid(memory_size)` in a trailing comment. Words after the last complete
+/// instruction are printed as `DATA` entries. The output can be read back with [`assemble`].
+pub fn disassemble(code: &[i32], memory_size: i32, isa: InstructionSet) -> String {
+    let width = code.len().saturating_sub(1).to_string().len();
+    let mut out = String::new();
+    let mut instructions = code.chunks_exact(3);
+    for (i, words) in instructions.by_ref().enumerate() {
+        let raw = format!("{} {}, {}, {}", isa.mnemonic(), words[0], words[1], words[2]);
+        let addresses: Vec<String> = words.iter().map(|w| w.rem_euclid(memory_size).to_string()).collect();
+        writeln!(out, "{:>width$}: {:<24} ; {}", i * 3, raw, addresses.join(", ")).unwrap();
+    }
+    let tail = code.len() - instructions.remainder().len();
+    for (i, word) in instructions.remainder().iter().enumerate() {
+        writeln!(out, "{:>width$}: DATA {}", tail + i, word).unwrap();
+    }
+    out
+}
+
+/// Parses SUBLEQ assembly into a program.
+///
+/// Each non-blank line is `SUBLEQ a, b, c` or `DATA v, ...`, optionally preceded by
+/// an `offset:` label, which must equal the position of the line's first word.
+/// `SUBNEG` and `ADDLEQ` are read like `SUBLEQ`, since every instruction set decodes
+/// the same words; the program does not record which one it was written for.
+/// Mnemonics are case-insensitive and everything after a `;` is a comment.
+pub fn assemble(src: &str) -> Result<SUBLEQProgram, AssembleError> {
+    let mut code = Vec::new();
+    for (index, text) in src.lines().enumerate() {
+        let line = index + 1;
+        let mut text = text.split(';').next().unwrap().trim();
+        if text.is_empty() {
+            continue;
+        }
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if label.parse::<usize>().ok() != Some(code.len()) {
+                return Err(AssembleError::Offset { line, expected: code.len(), found: label.to_string() });
+            }
+            text = rest.trim();
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands = operands.trim();
+        let values = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands
+                .split(',')
+                .map(|operand| {
+                    let operand = operand.trim();
+                    operand.parse::<i32>().map_err(|_| AssembleError::InvalidOperand { line, operand: operand.to_string() })
+                })
+                .collect::<Result<Vec<i32>, _>>()?
+        };
+
+        let valid_count = if InstructionSet::ALL.iter().any(|isa| mnemonic.eq_ignore_ascii_case(isa.mnemonic())) {
+            values.len() == 3
+        } else if mnemonic.eq_ignore_ascii_case("DATA") {
+            !values.is_empty()
+        } else {
+            return Err(AssembleError::UnknownMnemonic { line, mnemonic: mnemonic.to_string() });
+        };
+        if !valid_count {
+            return Err(AssembleError::OperandCount { line, mnemonic: mnemonic.to_uppercase(), found: values.len() });
+        }
+        code.extend(values);
+    }
+    Ok(SUBLEQProgram::from_code(code))
+}
+
+/// Why [`assemble`] rejected its input; every variant carries the 1-based line number.
+#[derive(Debug)]
+pub enum AssembleError {
+    UnknownMnemonic { line: usize, mnemonic: String },
+    OperandCount { line: usize, mnemonic: String, found: usize },
+    InvalidOperand { line: usize, operand: String },
+    Offset { line: usize, expected: usize, found: String },
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssembleError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {}: unknown mnemonic `{}`, expected SUBLEQ, SUBNEG, ADDLEQ or DATA", line, mnemonic)
+            }
+            AssembleError::OperandCount { line, mnemonic, found } if mnemonic != "DATA" => {
+                write!(f, "line {}: {} takes 3 operands, found {}", line, mnemonic, found)
+            }
+            AssembleError::OperandCount { line, mnemonic, .. } => {
+                write!(f, "line {}: {} takes at least one operand", line, mnemonic)
+            }
+            AssembleError::InvalidOperand { line, operand } => {
+                write!(f, "line {}: `{}` is not a valid integer operand", line, operand)
+            }
+            AssembleError::Offset { line, expected, found } => {
+                write!(f, "line {}: offset `{}` does not match position {}", line, found, expected)
+            }
+        }
+    }
+}
+
+impl Error for AssembleError {}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::testing::{proptest_config, valid_code};
+
+    proptest! {
+        #![proptest_config(proptest_config())]
+
+        #[test]
+        fn disassembly_assembles_back(code in valid_code(), isa in prop::sample::select(InstructionSet::ALL.to_vec())) {
+            let listing = disassemble(&code, 64, isa);
+            let program = assemble(&listing).unwrap();
+            prop_assert_eq!(program.code(), &code[..]);
+        }
+    }
+
+    #[test]
+    fn reads_trailing_data() {
+        let listing = disassemble(&[1, -2, 3, 70, -5], 64, InstructionSet::Subleq);
+        assert_eq!(listing, "0: SUBLEQ 1, -2, 3          ; 1, 62, 3\n3: DATA 70\n4: DATA -5\n");
+        assert_eq!(assemble(&listing).unwrap().code(), [1, -2, 3, 70, -5]);
+    }
+}
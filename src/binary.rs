@@ -0,0 +1,59 @@
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::SUBLEQProgram;
+
+/// First bytes of a binary program file.
+pub const PROGRAM_MAGIC: [u8; 4] = *b"SQPG";
+
+/// First bytes of a binary checkpoint.
+pub const CHECKPOINT_MAGIC: [u8; 4] = *b"SQCK";
+
+/// Layout version written into binary program files; bump it whenever the layout changes.
+pub const PROGRAM_BIN_VERSION: u32 = 1;
+
+/// A program with every field present, since bincode cannot skip the ones JSON omits.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Genome {
+    code: Vec<i32>,
+    mutation_rate: f64,
+    id: u64,
+    parents: Option<(u64, u64)>,
+}
+
+impl From<&SUBLEQProgram> for Genome {
+    fn from(program: &SUBLEQProgram) -> Self {
+        Genome { code: program.code.clone(), mutation_rate: program.mutation_rate, id: program.id, parents: program.parents }
+    }
+}
+
+impl From<Genome> for SUBLEQProgram {
+    fn from(genome: Genome) -> Self {
+        SUBLEQProgram { code: genome.code, mutation_rate: genome.mutation_rate, id: genome.id, parents: genome.parents }
+    }
+}
+
+// Variable-length integers store most genes, which are small next to i32::MAX, in one or two bytes
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+/// `magic`, then `version` as a little-endian `u32`, then `value` in bincode.
+pub(crate) fn encode<T: Serialize>(magic: [u8; 4], version: u32, value: &T) -> Vec<u8> {
+    let mut bytes = magic.to_vec();
+    bytes.extend(version.to_le_bytes());
+    options().serialize_into(&mut bytes, value).expect("binary files always serialize");
+    bytes
+}
+
+/// The version in the header of `bytes`, or `None` if they do not start with `magic`.
+pub(crate) fn version(bytes: &[u8], magic: [u8; 4]) -> Option<u32> {
+    let header = bytes.strip_prefix(&magic)?.first_chunk::<4>()?;
+    Some(u32::from_le_bytes(*header))
+}
+
+/// The value following a header checked with [`version`].
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> bincode::Result<T> {
+    options().deserialize(&bytes[8..])
+}
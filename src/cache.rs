@@ -0,0 +1,119 @@
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+
+use crate::par::*;
+use crate::{stream_rng, Evaluation, Fitness, SUBLEQProgram, VmConfig};
+
+/// Fitness scores memoized by genome, so repeated genomes skip the VM.
+///
+/// Scores depend only on the code, the VM settings and the fitness objective, so
+/// one cache must not be shared between runs that differ in those. An objective that
+/// makes random draws keeps the score of a genome's first evaluation.
+/// Entries are keyed on the raw code rather than its [`canonicalize`](crate::canonicalize)d
+/// form, because raw words can matter as data.
+pub struct FitnessCache {
+    entries: LruCache<Vec<i32>, usize>,
+    hits: u64,
+    misses: u64,
+}
+
+impl FitnessCache {
+    /// A cache holding at most `capacity` genomes, or unbounded if `None` (or `Some(0)`,
+    /// which [`GaConfig::validate`](crate::GaConfig::validate) rejects).
+    pub fn new(capacity: Option<usize>) -> Self {
+        let entries = match capacity.and_then(NonZeroUsize::new) {
+            Some(capacity) => LruCache::new(capacity),
+            None => LruCache::unbounded(),
+        };
+        FitnessCache { entries, hits: 0, misses: 0 }
+    }
+
+    /// Scores the whole population, executing only genomes that are not cached.
+    /// Genome `i` is scored with stream `i` of a generator seeded with 0; see
+    /// [`Fitness::score_with_rng`].
+    pub fn evaluate(&mut self, population: &[SUBLEQProgram], vm: &VmConfig, fitness: &dyn Fitness) -> Vec<usize> {
+        self.evaluate_executed(population, vm, fitness, 0, false).0
+    }
+
+    /// [`evaluate`](Self::evaluate) with streams of `seed`, also returning what executing
+    /// the genomes that were not cached found, by index; see [`SUBLEQProgram::assess`].
+    pub(crate) fn evaluate_executed(
+        &mut self,
+        population: &[SUBLEQProgram],
+        vm: &VmConfig,
+        fitness: &dyn Fitness,
+        seed: u64,
+        verify: bool,
+    ) -> (Vec<usize>, Vec<(usize, Evaluation)>) {
+        let cached: Vec<Option<usize>> = population.iter().map(|p| self.entries.get(p.code()).copied()).collect();
+
+        let computed: Vec<(usize, Evaluation)> = cached
+            .par_iter()
+            .enumerate()
+            .filter(|(_, score)| score.is_none())
+            .map(|(i, _)| (i, population[i].assess(vm, fitness, &mut stream_rng(seed, i), verify)))
+            .collect();
+        self.misses += computed.len() as u64;
+        self.hits += (population.len() - computed.len()) as u64;
+
+        let mut scores: Vec<usize> = cached.into_iter().map(|score| score.unwrap_or(0)).collect();
+        for (i, evaluation) in &computed {
+            scores[*i] = evaluation.score;
+            self.entries.put(population[*i].code().to_vec(), evaluation.score);
+        }
+        (scores, computed)
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Fraction of lookups answered from the cache, or 0 before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use rand::{Rng, RngCore};
+
+    use super::*;
+    use crate::{ExecutionResult, GaConfig};
+
+    /// Scores every program with a random draw, so scores follow the streams alone.
+    struct Lottery;
+
+    impl Fitness for Lottery {
+        fn score(&self, _result: &ExecutionResult<&mut [i32]>, _program: &SUBLEQProgram) -> usize {
+            0
+        }
+
+        fn score_with_rng(&self, _result: &ExecutionResult<&mut [i32]>, _program: &SUBLEQProgram, rng: &mut dyn RngCore) -> usize {
+            rng.gen_range(0..1_000_000)
+        }
+    }
+
+    #[test]
+    fn scores_the_same_at_any_thread_count() {
+        let vm = GaConfig::default().vm();
+        let population: Vec<SUBLEQProgram> = (0..64).map(|i| SUBLEQProgram::from_code(vec![i, i + 1, i + 2])).collect();
+        let scores = |threads| {
+            let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| FitnessCache::new(None).evaluate_executed(&population, &vm, &Lottery, 7, false).0)
+        };
+        let single = scores(1);
+        assert_eq!(scores(4), single);
+        assert!(single.windows(2).any(|pair| pair[0] != pair[1]), "the draws differ between individuals");
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use crate::vm::address;
+use crate::{InstructionSet, VmConfig};
+
+/// A normal form of `code` under which some behaviorally identical genomes compare equal
+/// when run on `vm`.
+///
+/// Two equivalences are applied:
+///
+/// - Every word is reduced modulo `memory_size`, since operands are only ever used
+///   as addresses after such a reduction. `-1` and `memory_size - 1` therefore match.
+/// - Trailing words past the last instruction reachable from the load address are
+///   dropped. Reachability follows both the fall-through and the branch target of
+///   every instruction, except where the branch is decided in advance: an instruction
+///   subtracting a cell other than the I/O port from itself stores zero, so it always
+///   branches under SUBLEQ and never does under SUBNEG.
+///
+/// Both assume the program is read only as instructions. Not detected, or detected
+/// wrongly, are: words whose raw value matters as data, including to the fitness
+/// objective and to `halt_on_negative`; code that rewrites its own instructions,
+/// which can reach instructions the static walk cannot; memory outside the code that
+/// leads back into it, as with `wrap_pc` or a nonzero `background`; and any equivalence
+/// between different instructions, such as two that both only clear a cell. Use it for
+/// population metrics, not to decide whether two programs score alike.
+pub fn canonicalize(code: &[i32], vm: &VmConfig) -> Vec<i32> {
+    let size = vm.memory_size;
+    // Branch targets are real addresses, while the walk indexes the code
+    let offset = vm.load_offset % size as usize;
+    let mut reachable = HashSet::new();
+    let mut pending = vec![0];
+    let mut end = 0;
+    while let Some(pc) = pending.pop() {
+        // Memory past the code starts zeroed and is assumed never to lead back into it
+        if pc >= code.len() || !reachable.insert(pc) {
+            continue;
+        }
+        end = end.max((pc + 3).min(code.len()));
+        let Some(&c) = code.get(pc + 2) else {
+            continue;
+        };
+        let (a, b) = (address(code[pc], size), address(code[pc + 1], size));
+        let clears = a == b && vm.io_port != Some(a);
+        let (falls_through, branches) = match vm.isa {
+            InstructionSet::Subleq if clears => (false, true),
+            InstructionSet::Subneg if clears => (true, false),
+            _ => (true, true),
+        };
+        if falls_through {
+            pending.push(pc + 3);
+        }
+        if branches {
+            pending.push((address(c, size) + size as usize - offset) % size as usize);
+        }
+    }
+    code[..end].iter().map(|&word| address(word, size) as i32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GaConfig, SUBLEQProgram};
+
+    fn vm() -> VmConfig {
+        GaConfig { memory_size: 16, ..GaConfig::default() }.vm()
+    }
+
+    #[test]
+    fn drops_padding_past_a_branch_that_is_always_taken() {
+        // Clears cell 9 and loops, so the second instruction never runs
+        assert_eq!(canonicalize(&[9, 9, 0, 1, 2, 3, 4], &vm()), [9, 9, 0]);
+        assert_eq!(canonicalize(&[9, 9, 0, 1, 2, 3], &VmConfig { isa: InstructionSet::Subneg, ..vm() }), [9, 9, 0, 1, 2, 3]);
+        assert_eq!(canonicalize(&[9, 9, 0, 1, 2, 3], &VmConfig { io_port: Some(9), ..vm() }), [9, 9, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn follows_branches_from_the_load_address() {
+        // Jumps from real address 4 over the words at 7..10 to 10
+        let code = [9, 9, 10, 1, 2, 3, 15, 15, 6];
+        assert_eq!(canonicalize(&code, &VmConfig { load_offset: 4, ..vm() }), code);
+        assert_eq!(canonicalize(&code, &vm()), [9, 9, 10]);
+    }
+
+    #[test]
+    fn reduces_words_modulo_memory() {
+        assert_eq!(canonicalize(&[-1, 2, 3], &vm()), canonicalize(&[15, 2, 3], &vm()));
+        assert_eq!(canonicalize(&[-1, 2, -13], &vm()), [15, 2, 3]);
+    }
+
+    #[test]
+    fn misses_what_the_walk_cannot_see() {
+        // halt_on_negative tells a target of -1 from 15, but the forms match
+        assert_eq!(canonicalize(&[1, 2, -1], &vm()), canonicalize(&[1, 2, 15], &vm()));
+        // Clearing cell 9 or cell 10 does the same when both start at zero, yet the forms differ
+        assert_ne!(canonicalize(&[9, 9, 3], &vm()), canonicalize(&[10, 10, 3], &vm()));
+        // The first instruction turns the loop at 3 into a jump to 6 by subtracting -3
+        // from its target, which the walk still reads as 3
+        let rewrites = [5, 10, 3, 8, 8, 3, 1, 2, 3, 0, -3];
+        assert_eq!(canonicalize(&rewrites, &vm()), [5, 10, 3, 8, 8, 3]);
+        let (trace, _) = SUBLEQProgram::from_code(rewrites.to_vec()).execute_trace(&vm());
+        assert!(trace.iter().any(|record| record.pc == 6));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "bincode")]
+use crate::binary::{self, Genome, CHECKPOINT_MAGIC};
+use crate::{FileFormat, RunError, SUBLEQProgram};
+
+/// Format version written into every checkpoint; bump it whenever the layout changes.
+pub const CHECKPOINT_VERSION: u32 = 3;
+
+/// Everything [`run`](crate::run) needs to continue from the start of `generation`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Checkpoint<P = Vec<SUBLEQProgram>, G = SUBLEQProgram, R = ChaCha12Rng> {
+    pub(crate) version: u32,
+    pub(crate) generation: usize,
+    pub(crate) population: P,
+    pub(crate) progress: Progress<G>,
+    pub(crate) rng: R,
+}
+
+/// What the run found before `generation`, which the stopping rules and the report use.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Progress<G = SUBLEQProgram> {
+    /// The fittest program of any generation so far, and its score.
+    pub(crate) best: Option<(G, usize)>,
+    /// Generation the best score last rose in.
+    pub(crate) last_improvement: usize,
+    /// Generation the search last started over from random programs.
+    pub(crate) restarted_at: usize,
+    /// Generation a program first wrote a full copy of itself in, if any has.
+    pub(crate) first_full_copy: Option<usize>,
+}
+
+#[cfg(feature = "bincode")]
+impl<G> Progress<G> {
+    /// Converts the best program, as between programs and their bincode [`Genome`]s.
+    fn map<H>(self, f: impl FnOnce(G) -> H) -> Progress<H> {
+        let Progress { best, last_improvement, restarted_at, first_full_copy } = self;
+        Progress { best: best.map(|(program, score)| (f(program), score)), last_improvement, restarted_at, first_full_copy }
+    }
+}
+
+/// Writes a checkpoint in `format` through a temporary file, so an interrupted write
+/// never replaces the previous checkpoint with a truncated one.
+pub(crate) fn save(
+    path: &Path,
+    generation: usize,
+    population: &[SUBLEQProgram],
+    progress: Progress<&SUBLEQProgram>,
+    rng: &ChaCha12Rng,
+    format: FileFormat,
+) -> Result<(), RunError> {
+    let bytes = match format {
+        FileFormat::Json => {
+            let checkpoint = Checkpoint { version: CHECKPOINT_VERSION, generation, population, progress, rng };
+            serde_json::to_vec(&checkpoint).expect("checkpoints always serialize")
+        }
+        #[cfg(feature = "bincode")]
+        FileFormat::Bincode => {
+            let population: Vec<Genome> = population.iter().map(Genome::from).collect();
+            let progress = progress.map(Genome::from);
+            let checkpoint = Checkpoint { version: CHECKPOINT_VERSION, generation, population, progress, rng };
+            binary::encode(CHECKPOINT_MAGIC, CHECKPOINT_VERSION, &checkpoint)
+        }
+        #[cfg(not(feature = "bincode"))]
+        FileFormat::Bincode => unreachable!("GaConfig::validate rejects bincode without the bincode feature"),
+        FileFormat::Asm => unreachable!("GaConfig::validate rejects assembly checkpoints"),
+    };
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes)
+        .and_then(|()| fs::rename(&partial, path))
+        .map_err(|e| RunError::Checkpoint(path.to_path_buf(), e))
+}
+
+/// Reads a checkpoint in either format, rejecting it if any of its programs would not
+/// fit in `memory_size` cells.
+pub(crate) fn load(path: &Path, memory_size: i32) -> Result<Checkpoint, RunError> {
+    let bytes = fs::read(path).map_err(|e| RunError::Checkpoint(path.to_path_buf(), e))?;
+    let checkpoint = parse(path, &bytes)?;
+    if let Some(program) = checkpoint.population.iter().find(|p| p.code().len() > memory_size as usize) {
+        return Err(RunError::CheckpointExceedsMemory { path: path.to_path_buf(), len: program.code().len(), memory_size });
+    }
+    Ok(checkpoint)
+}
+
+fn parse(path: &Path, bytes: &[u8]) -> Result<Checkpoint, RunError> {
+    #[cfg(feature = "bincode")]
+    if let Some(version) = binary::version(bytes, CHECKPOINT_MAGIC) {
+        if version != CHECKPOINT_VERSION {
+            return Err(RunError::CheckpointVersion { path: path.to_path_buf(), version: version.into() });
+        }
+        let checkpoint: Checkpoint<Vec<Genome>, Genome> = binary::decode(bytes).map_err(|e| RunError::InvalidBinaryCheckpoint(path.to_path_buf(), e))?;
+        let population = checkpoint.population.into_iter().map(SUBLEQProgram::from).collect();
+        let progress = checkpoint.progress.map(SUBLEQProgram::from);
+        return Ok(Checkpoint { version, generation: checkpoint.generation, population, progress, rng: checkpoint.rng });
+    }
+    let text = std::str::from_utf8(bytes).map_err(|e| RunError::Checkpoint(path.to_path_buf(), io::Error::new(io::ErrorKind::InvalidData, e)))?;
+    let invalid = |e| RunError::InvalidCheckpoint(path.to_path_buf(), e);
+    // Check the version before the layout, so an old file reports that rather than a missing field
+    let value: serde_json::Value = serde_json::from_str(text).map_err(invalid)?;
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version != CHECKPOINT_VERSION as u64 {
+        return Err(RunError::CheckpointVersion { path: path.to_path_buf(), version });
+    }
+    serde_json::from_value(value).map_err(invalid)
+}
@@ -0,0 +1,57 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::par::*;
+use crate::stats::{edit_distance_within, rank_key};
+use crate::SUBLEQProgram;
+
+/// A group of similar programs from one population, found by [`cluster`].
+#[derive(Clone, Debug, Serialize)]
+pub struct Cluster {
+    /// Fittest member, the one every other member lies within the radius of.
+    pub representative: Vec<i32>,
+    pub best_fitness: usize,
+    /// Number of programs in the cluster, counting copies of a genome separately.
+    pub size: usize,
+    /// Distinct genomes in the cluster, fittest first.
+    pub members: Vec<Vec<i32>>,
+}
+
+/// Groups a scored population into clusters of programs within `radius` edits
+/// ([`program_distance`](crate::program_distance)) of the cluster's fittest member,
+/// largest cluster first and the fittest first among equally large ones.
+///
+/// Distinct genomes are taken fittest first, ties broken as for the run's best program,
+/// and each joins the first cluster whose representative is close enough or else
+/// founds a new one. The greedy pass compares every genome with every representative,
+/// so it is quadratic in the number of clusters, but each comparison stops as soon as
+/// the distance is known to exceed the radius.
+pub fn cluster(population: &[SUBLEQProgram], fitness_scores: &[usize], radius: usize) -> Vec<Cluster> {
+    let mut copies: HashMap<&[i32], (usize, &SUBLEQProgram, usize)> = HashMap::new();
+    for (program, &fitness) in population.iter().zip(fitness_scores) {
+        let entry = copies.entry(program.code()).or_insert((0, program, fitness));
+        entry.0 += 1;
+        entry.2 = entry.2.max(fitness);
+    }
+    let mut genomes: Vec<_> = copies.into_values().collect();
+    genomes.sort_unstable_by(|a, b| rank_key(a.1, a.2).cmp(&rank_key(b.1, b.2)));
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for (count, program, fitness) in genomes {
+        let code = program.code();
+        let near = clusters
+            .par_iter()
+            .position_first(|c| c.representative.len().abs_diff(code.len()) <= radius && edit_distance_within(&c.representative, code, radius).is_some());
+        match near {
+            Some(i) => {
+                clusters[i].size += count;
+                clusters[i].members.push(code.to_vec());
+            }
+            None => clusters.push(Cluster { representative: code.to_vec(), best_fitness: fitness, size: count, members: vec![code.to_vec()] }),
+        }
+    }
+    clusters.sort_by_key(|c| Reverse(c.size));
+    clusters
+}
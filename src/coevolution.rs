@@ -0,0 +1,73 @@
+use crate::{copy_offsets, Fitness, SUBLEQProgram, VmConfig};
+
+/// One host run with a parasite beside it in the same memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encounter {
+    /// Score of the host under the run's objective.
+    pub host: usize,
+    /// Words of the parasite that the host wrote: every word of its full copies if it
+    /// made any, else its longest partial copy.
+    pub parasite: usize,
+}
+
+/// Address of the parasite relative to the host's, halfway round memory, so that two
+/// programs of up to half the memory never overlap.
+pub fn parasite_offset(memory_size: i32) -> usize {
+    memory_size as usize / 2
+}
+
+/// Runs `host` as usual, but in a memory that also holds `parasite` at
+/// [`parasite_offset`] past the host's load address. Only the host's program counter
+/// runs, so the parasite executes only where the host jumps into it, and is copied only
+/// by instructions that copy its cells. `protect_code` protects the host's cells alone.
+pub fn encounter(host: &SUBLEQProgram, parasite: &SUBLEQProgram, vm: &VmConfig, fitness: &dyn Fitness) -> Encounter {
+    let size = vm.memory_size as usize;
+    let home = parasite_offset(vm.memory_size);
+    // The arena as the host will see it, rotated to start at its load address
+    let mut initial: Vec<i32> = if vm.background.is_empty() {
+        vec![0; size]
+    } else {
+        vm.background.iter().copied().cycle().skip(vm.load_offset).take(size).collect()
+    };
+    initial[home..home + parasite.code().len()].copy_from_slice(parasite.code());
+    initial[..host.code().len()].copy_from_slice(host.code());
+    let mut background = initial.clone();
+    background.rotate_right(vm.load_offset);
+    let arena = VmConfig { background, ..vm.clone() };
+    host.with_execution(&arena, |result| Encounter {
+        host: fitness.score(&result, host),
+        parasite: hijacked(&initial, result.memory, parasite.code()),
+    })
+}
+
+/// Words of `code` the run wrote into `memory`, measured as [`Encounter::parasite`]
+/// describes; matches already in `initial`, such as the parasite itself, do not count.
+fn hijacked(initial: &[i32], memory: &[i32], code: &[i32]) -> usize {
+    let copies = copy_offsets(memory, code, 0).into_iter().filter(|&at| initial[at..at + code.len()] != *code).count();
+    if copies > 0 {
+        return copies * code.len();
+    }
+    let matched = |memory: &[i32], at: usize| memory[at..].iter().zip(code).take_while(|(cell, gene)| cell == gene).count();
+    (0..memory.len())
+        .map(|at| matched(memory, at))
+        .zip((0..initial.len()).map(|at| matched(initial, at)))
+        .filter(|&(after, before)| after > before)
+        .map(|(after, _)| after)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Final state of a run of [`run_coevolution`](crate::run_coevolution).
+pub struct Coevolution {
+    /// Number of generations scored.
+    pub generations: usize,
+    pub hosts: Vec<SUBLEQProgram>,
+    /// Score of each host in its last encounter.
+    pub host_scores: Vec<usize>,
+    pub parasites: Vec<SUBLEQProgram>,
+    /// Words each parasite got copied in its last encounter.
+    pub parasite_scores: Vec<usize>,
+    /// The highest scoring host and parasite of any generation, the earliest on ties.
+    pub best_host: Option<(SUBLEQProgram, usize)>,
+    pub best_parasite: Option<(SUBLEQProgram, usize)>,
+}
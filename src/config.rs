@@ -0,0 +1,1025 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{parasite_offset, AdaptiveMutation, Crossover, FileFormat, FitnessMode, InstructionSet, LengthBias, MemoryInit, MutationConfig, MutationOperator, Replacement, SUBLEQProgram, Selection, VmConfig};
+
+const POPULATION_SIZE: usize = 10000;
+const GENERATIONS: usize = 10000;
+const MUTATION_RATE: f64 = 0.05;
+const MIN_PROGRAM_LENGTH: usize = 6;
+const MAX_PROGRAM_LENGTH: usize = 64;
+const MEMORY_SIZE: i32 = 256;
+const MAX_EXECUTION_STEPS: usize = 1000;
+const TOURNAMENT_SIZE: usize = 3;
+const RANK_PRESSURE: f64 = 1.5;
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+const HALL_OF_FAME_SIZE: usize = 10;
+const DIVERSITY_SAMPLES: usize = 1000;
+const MIGRATE_EVERY: usize = 10;
+const GAP: f64 = 0.1;
+const BLOCK_RATE: f64 = 0.1;
+const HYPERMUTATION_FACTOR: f64 = 5.0;
+const HYPERMUTATION_GENERATIONS: usize = 10;
+const INITIAL_TEMP: f64 = 10.0;
+const COOLING: f64 = 0.999;
+const FIND_DISTANCE: usize = 8;
+const SOUP_SIZE: usize = 8192;
+const SOUP_SLICE: usize = 20;
+
+/// What kind of simulation to run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// Evolve a population of separately executed programs.
+    #[default]
+    Generational,
+    /// Run programs side by side in one shared arena, where they reproduce by copying themselves.
+    Soup,
+    /// Replace only the worst `gap` fraction of the population each iteration, keeping the rest.
+    SteadyState,
+    /// Mutation-only hill climbing from one random program, as a baseline without crossover.
+    Hillclimb,
+    /// Hill climbing that also accepts worse mutants by the Metropolis criterion, at a
+    /// temperature that cools every iteration.
+    Anneal,
+    /// Evolve hosts scored on the objective against parasites scored on getting the
+    /// hosts to copy them, each host run with a parasite in its memory.
+    Coevolution,
+}
+
+/// Parameters of one evolutionary run.
+///
+/// Use [`GaConfig::builder`] to construct a validated config, or
+/// [`GaConfig::load`] to read one from a TOML file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GaConfig {
+    /// Genetic algorithm or shared-memory soup.
+    pub mode: Mode,
+    /// Number of programs in each generation, or initially in the soup.
+    pub population: usize,
+    /// Maximum number of generations before giving up; rounds in soup mode.
+    pub generations: usize,
+    /// Stop once the best fitness has not improved for this many generations; `None` never stops early.
+    /// Checkpoints record the last improvement, so a resumed run stops where it would have.
+    pub stall_generations: Option<usize>,
+    /// Wall-clock budget after which no further generation is started, written like
+    /// `30s` or `5m` in TOML (see [`parse_duration`]); `None` has no limit.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub max_time: Option<Duration>,
+    /// Raise mutation rates once the best fitness has not improved for this many
+    /// generations; `None` never does. Hypermutation is not checkpointed, so a resumed
+    /// run starts with it off and counts the stall from the generation it resumes at.
+    pub stall_trigger: Option<usize>,
+    /// Factor mutation rates are multiplied by during hypermutation.
+    pub hypermutation_factor: f64,
+    /// Number of generations hypermutation lasts once triggered.
+    pub hypermutation_generations: usize,
+    /// Number of times the hill climber starts over from a new random program when it
+    /// stalls, before stopping.
+    pub restarts: usize,
+    /// Starting temperature in anneal mode, at which a mutant scoring `loss` less than
+    /// the current program is accepted with probability `exp(-loss / temperature)`.
+    pub initial_temp: f64,
+    /// Factor the annealing temperature is multiplied by every iteration; a restart
+    /// starts again from `initial_temp`.
+    pub cooling: f64,
+    /// Number of distinct solutions to collect before stopping; after each one the
+    /// search starts over from random programs. Ignored in soup mode. Solutions are not
+    /// checkpointed, so a resumed run collects `find` more.
+    pub find: usize,
+    /// Smallest [`program_distance`](crate::program_distance) from every earlier solution
+    /// that makes a new one distinct is one more than this.
+    pub find_distance: usize,
+    /// Check every program executed in a generation against the objective, not just
+    /// the fittest, and take the shortest that meets it. Ignored by the hill climber.
+    pub verify_all: bool,
+    /// Per-gene probability of being replaced during mutation; with self-adaptation
+    /// this is only the starting rate of each program.
+    pub mutation_rate: f64,
+    /// Learning rate of the lognormal per-program mutation-rate adaptation; 0 disables it.
+    pub self_adaptation: f64,
+    /// Rule adjusting the mutation rate of the whole population from the offspring's
+    /// success; requires `self_adaptation` to be 0. The adjusted rate is not checkpointed,
+    /// so a resumed run starts again from `mutation_rate`.
+    pub adaptive_mutation: AdaptiveMutation,
+    /// Probability per mutation of inserting or deleting one instruction.
+    pub indel_rate: f64,
+    /// Operators applied to each child.
+    pub mutation_operator: MutationOperator,
+    /// Probability per mutation of a block mutation, when `mutation_operator` includes it.
+    pub block_rate: f64,
+    /// Shortest program length, both initially and after mutation.
+    pub min_len: usize,
+    /// Longest program length, both initially and after mutation.
+    pub max_len: usize,
+    /// Which lengths between `min_len` and `max_len` random initial programs favour.
+    pub init_length_bias: LengthBias,
+    /// Number of memory cells in the VM; also bounds gene values to `-memory_size..memory_size`.
+    pub memory_size: i32,
+    /// Maximum number of instructions executed per program.
+    pub max_steps: usize,
+    /// Halt on a taken branch to a negative address instead of wrapping it.
+    pub halt_on_negative: bool,
+    /// Address of the memory-mapped I/O cell (see [`VmConfig::io_port`]); `None` disables I/O.
+    pub io_port: Option<usize>,
+    /// Values programs read through the I/O port.
+    pub input: Vec<i32>,
+    /// End execution early when a program revisits an earlier machine state.
+    pub detect_cycles: bool,
+    /// Instruction executed by the machine.
+    pub isa: InstructionSet,
+    /// Wrap the program counter around memory instead of halting at the end.
+    pub wrap_pc: bool,
+    /// Restart at address 0 instead of halting at the end of memory (see
+    /// [`VmConfig::restart_at_end`]). Not used by the soup, where running off the end
+    /// is how organisms die.
+    pub restart_at_end: bool,
+    /// Make the program's own cells read-only, so it can only write into data memory.
+    pub protect_code: bool,
+    /// Address programs are loaded at and start from (see [`VmConfig::load_offset`]).
+    pub load_offset: usize,
+    /// What memory holds around the program (see [`VmConfig::background`]). Random
+    /// memory is drawn from `seed`, or from 0 without one, so a replay needs the same
+    /// seed. Not used by the soup.
+    pub memory_init: MemoryInit,
+    /// Values repeated through memory with `memory_init` pattern.
+    pub memory_pattern: Vec<i32>,
+    /// Program placed in the initial population in place of random individuals.
+    pub seed_program: Option<Vec<i32>>,
+    /// Number of copies of `seed_program` placed, spread evenly through the population.
+    pub seed_copies: usize,
+    /// Seed for the random number generator; `None` seeds from the OS.
+    pub seed: Option<u64>,
+    /// Number of sub-populations that evolve separately, exchanging migrants along a ring.
+    pub islands: usize,
+    /// Generations between migrations.
+    pub migrate_every: usize,
+    /// Number of top individuals each island sends to the next one per migration.
+    pub migrants: usize,
+    /// Strategy used to pick parents for the next generation.
+    pub selection: Selection,
+    /// Select on copied words, length and steps as separate objectives, ranking the
+    /// population NSGA-II style by Pareto front and crowding distance, instead of on
+    /// fitness. Re-executes every program once more per generation.
+    pub multiobjective: bool,
+    /// Number of individuals competing in each parent-selection tournament; 1 picks uniformly.
+    pub tournament_size: usize,
+    /// Expected number of offspring of the fittest individual under rank selection, from
+    /// 1 (no pressure) to 2; the least fit gets `2 - rank_pressure`.
+    pub rank_pressure: f64,
+    /// Number of top individuals copied unchanged into the next generation; clamped below `population`.
+    pub elitism: usize,
+    /// Number of the weakest offspring replaced by random programs each generation,
+    /// per island; clamped so that elites and at least one offspring survive.
+    pub immigrants: usize,
+    /// Fraction of the population replaced per iteration in steady-state mode.
+    pub gap: f64,
+    /// Which individuals the offspring replace in steady-state mode. Crowding children
+    /// waiting to be compared with the individuals they displaced are not checkpointed,
+    /// so on resuming those children stay whatever they score.
+    pub replacement: Replacement,
+    /// Operator used to recombine two parents.
+    pub crossover: Crossover,
+    /// Breed each child by cloning one selected parent and mutating it, skipping
+    /// `crossover`, to measure what recombination contributes.
+    pub no_crossover: bool,
+    /// Breed offspring in parallel, each from its own random stream. Seeded runs are
+    /// still reproducible, but follow a different trajectory than sequential breeding.
+    pub parallel_breeding: bool,
+    /// Number of threads for parallel fitness evaluation and breeding; 0 uses rayon's
+    /// global pool, which has one per core. Ignored without the `parallel` feature.
+    pub threads: usize,
+    /// How executed memory is scored.
+    pub fitness: FitnessMode,
+    /// Fitness subtracted per word of code from programs without a full copy.
+    pub parsimony: f64,
+    /// Number of single-gene hill-climbing moves tried on each new child; 0 disables
+    /// this local search, which costs one execution per move.
+    pub local_search: usize,
+    /// Memoize fitness by genome instead of re-executing repeated programs.
+    pub cache: bool,
+    /// Maximum number of genomes kept in the fitness cache, evicting the least recently used; `None` is unbounded.
+    pub cache_size: Option<usize>,
+    /// CSV file receiving per-generation fitness statistics.
+    pub log_csv: Option<PathBuf>,
+    /// JSON Lines file receiving the same statistics plus the best genome, one object per generation.
+    pub log_jsonl: Option<PathBuf>,
+    /// Number of random pairs compared to estimate population diversity each generation.
+    pub diversity_samples: usize,
+    /// Write a checkpoint every this many generations; `None` disables checkpointing.
+    pub checkpoint_every: Option<usize>,
+    /// File that checkpoints are written to, replacing the previous one.
+    pub checkpoint_file: PathBuf,
+    /// Encoding of checkpoints and saved programs; bincode needs the `bincode` feature, and
+    /// asm can only hold programs.
+    pub format: FileFormat,
+    /// Checkpoint to continue from instead of creating a new population.
+    pub resume: Option<PathBuf>,
+    /// JSON file receiving the best distinct genomes of the run, and every solution it
+    /// discovered, when it ends.
+    pub hall_of_fame: Option<PathBuf>,
+    /// Number of genomes kept in the hall of fame.
+    pub hall_of_fame_size: usize,
+    /// Give every individual an id and record its parents, so a replicator's ancestry can be traced.
+    pub track_lineage: bool,
+    /// Number of cells in the soup arena.
+    pub soup_size: usize,
+    /// Instructions each soup organism executes per turn.
+    pub soup_slice: usize,
+}
+
+impl Default for GaConfig {
+    fn default() -> Self {
+        GaConfig {
+            mode: Mode::Generational,
+            population: POPULATION_SIZE,
+            generations: GENERATIONS,
+            stall_generations: None,
+            max_time: None,
+            stall_trigger: None,
+            hypermutation_factor: HYPERMUTATION_FACTOR,
+            hypermutation_generations: HYPERMUTATION_GENERATIONS,
+            restarts: 0,
+            initial_temp: INITIAL_TEMP,
+            cooling: COOLING,
+            find: 1,
+            find_distance: FIND_DISTANCE,
+            verify_all: false,
+            mutation_rate: MUTATION_RATE,
+            self_adaptation: 0.0,
+            adaptive_mutation: AdaptiveMutation::Off,
+            indel_rate: 0.0,
+            mutation_operator: MutationOperator::Point,
+            block_rate: BLOCK_RATE,
+            min_len: MIN_PROGRAM_LENGTH,
+            max_len: MAX_PROGRAM_LENGTH,
+            init_length_bias: LengthBias::Uniform,
+            memory_size: MEMORY_SIZE,
+            max_steps: MAX_EXECUTION_STEPS,
+            halt_on_negative: false,
+            io_port: None,
+            input: Vec::new(),
+            detect_cycles: false,
+            isa: InstructionSet::Subleq,
+            wrap_pc: false,
+            restart_at_end: false,
+            protect_code: false,
+            load_offset: 0,
+            memory_init: MemoryInit::Zero,
+            memory_pattern: Vec::new(),
+            seed_program: None,
+            seed_copies: 1,
+            seed: None,
+            islands: 1,
+            migrate_every: MIGRATE_EVERY,
+            migrants: 1,
+            selection: Selection::Tournament,
+            multiobjective: false,
+            tournament_size: TOURNAMENT_SIZE,
+            rank_pressure: RANK_PRESSURE,
+            elitism: 0,
+            immigrants: 0,
+            gap: GAP,
+            replacement: Replacement::Worst,
+            crossover: Crossover::Single,
+            no_crossover: false,
+            parallel_breeding: false,
+            threads: 0,
+            fitness: FitnessMode::Prefix,
+            parsimony: 0.0,
+            local_search: 0,
+            cache: false,
+            cache_size: None,
+            log_csv: None,
+            log_jsonl: None,
+            diversity_samples: DIVERSITY_SAMPLES,
+            checkpoint_every: None,
+            checkpoint_file: PathBuf::from(CHECKPOINT_FILE),
+            format: FileFormat::Json,
+            resume: None,
+            hall_of_fame: None,
+            hall_of_fame_size: HALL_OF_FAME_SIZE,
+            track_lineage: false,
+            soup_size: SOUP_SIZE,
+            soup_slice: SOUP_SLICE,
+        }
+    }
+}
+
+impl GaConfig {
+    pub fn builder() -> GaConfigBuilder {
+        GaConfigBuilder { config: GaConfig::default() }
+    }
+
+    /// Reads a TOML file; keys it leaves out keep their default values.
+    ///
+    /// The result is not validated, so that it can be adjusted with
+    /// [`GaConfig::into_builder`] before calling [`GaConfigBuilder::build`].
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    pub fn into_builder(self) -> GaConfigBuilder {
+        GaConfigBuilder { config: self }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // Soup organisms need no partner, but crossover needs two parents
+        let min_population = if self.mode == Mode::Soup { 1 } else { 2 };
+        if self.population < min_population {
+            return Err(ConfigError::PopulationTooSmall { population: self.population, min: min_population });
+        }
+        // Each island breeds on its own, so it needs a full minimal population
+        let max_islands = self.population / min_population;
+        if self.islands == 0 || self.islands > max_islands {
+            return Err(ConfigError::Islands { islands: self.islands, max: max_islands });
+        }
+        if self.migrate_every == 0 {
+            return Err(ConfigError::MigrationInterval);
+        }
+        if !(self.gap > 0.0 && self.gap <= 1.0) {
+            return Err(ConfigError::Gap(self.gap));
+        }
+        if self.stall_trigger == Some(0) || self.hypermutation_generations == 0 {
+            return Err(ConfigError::Hypermutation);
+        }
+        if !(self.hypermutation_factor > 0.0 && self.hypermutation_factor.is_finite()) {
+            return Err(ConfigError::HypermutationFactor(self.hypermutation_factor));
+        }
+        if !(self.initial_temp > 0.0 && self.initial_temp.is_finite()) {
+            return Err(ConfigError::InitialTemp(self.initial_temp));
+        }
+        if !(self.cooling > 0.0 && self.cooling <= 1.0) {
+            return Err(ConfigError::Cooling(self.cooling));
+        }
+        if self.stall_generations == Some(0) {
+            return Err(ConfigError::StallGenerations);
+        }
+        if self.tournament_size == 0 {
+            return Err(ConfigError::EmptyTournament);
+        }
+        if !(1.0..=2.0).contains(&self.rank_pressure) {
+            return Err(ConfigError::RankPressure(self.rank_pressure));
+        }
+        if !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err(ConfigError::MutationRate(self.mutation_rate));
+        }
+        if self.self_adaptation < 0.0 {
+            return Err(ConfigError::SelfAdaptation(self.self_adaptation));
+        }
+        if self.adaptive_mutation != AdaptiveMutation::Off && self.self_adaptation > 0.0 {
+            return Err(ConfigError::AdaptiveMutation);
+        }
+        // Both compare a child's fitness against scores that would be front rankings
+        let compares_fitness = self.adaptive_mutation != AdaptiveMutation::Off || self.replacement == Replacement::Crowding;
+        if self.multiobjective && (!matches!(self.mode, Mode::Generational | Mode::SteadyState) || compares_fitness) {
+            return Err(ConfigError::Multiobjective);
+        }
+        if !(self.parsimony >= 0.0 && self.parsimony.is_finite()) {
+            return Err(ConfigError::Parsimony(self.parsimony));
+        }
+        if !(0.0..=1.0).contains(&self.indel_rate) {
+            return Err(ConfigError::IndelRate(self.indel_rate));
+        }
+        if !(0.0..=1.0).contains(&self.block_rate) {
+            return Err(ConfigError::BlockRate(self.block_rate));
+        }
+        if self.min_len == 0 || self.min_len > self.max_len {
+            return Err(ConfigError::LengthBounds { min_len: self.min_len, max_len: self.max_len });
+        }
+        if self.memory_size < 3 {
+            return Err(ConfigError::MemoryTooSmall(self.memory_size));
+        }
+        if self.max_len > self.memory_size as usize {
+            return Err(ConfigError::ProgramExceedsMemory { max_len: self.max_len, memory_size: self.memory_size });
+        }
+        if self.diversity_samples == 0 {
+            return Err(ConfigError::NoDiversitySamples);
+        }
+        if self.checkpoint_every == Some(0) {
+            return Err(ConfigError::CheckpointInterval);
+        }
+        if self.hall_of_fame_size == 0 {
+            return Err(ConfigError::EmptyHallOfFame);
+        }
+        if self.find == 0 {
+            return Err(ConfigError::NothingToFind);
+        }
+        if self.cache_size == Some(0) {
+            return Err(ConfigError::EmptyCache);
+        }
+        #[cfg(not(feature = "bincode"))]
+        if self.format == FileFormat::Bincode {
+            return Err(ConfigError::BincodeUnavailable);
+        }
+        if self.format == FileFormat::Asm && self.checkpoint_every.is_some() {
+            return Err(ConfigError::AsmCheckpoint);
+        }
+        if self.mode == Mode::Soup {
+            if self.memory_size as usize > self.soup_size {
+                return Err(ConfigError::SoupWindow { memory_size: self.memory_size, soup_size: self.soup_size });
+            }
+            let longest = self.seed_program.as_ref().map_or(0, Vec::len).max(self.max_len);
+            if self.population.saturating_mul(longest) > self.soup_size {
+                return Err(ConfigError::SoupTooSmall { population: self.population, max_len: longest, soup_size: self.soup_size });
+            }
+        }
+        if self.mode == Mode::Coevolution && self.max_len > parasite_offset(self.memory_size) {
+            return Err(ConfigError::CoevolutionArena { max_len: self.max_len, memory_size: self.memory_size });
+        }
+        if let Some(code) = self.seed_program.as_ref().filter(|code| code.len() > self.memory_size as usize) {
+            return Err(ConfigError::SeedExceedsMemory { len: code.len(), memory_size: self.memory_size });
+        }
+        // The soup never mutates lengths, so only evolved seeds must respect the bounds
+        if let Some(code) = self.seed_program.as_ref().filter(|code| self.mode != Mode::Soup && !(self.min_len..=self.max_len).contains(&code.len())) {
+            return Err(ConfigError::SeedLength { len: code.len(), min_len: self.min_len, max_len: self.max_len });
+        }
+        if self.seed_copies == 0 || self.seed_copies > self.population {
+            return Err(ConfigError::SeedCopies { copies: self.seed_copies, population: self.population });
+        }
+        if let Some(port) = self.io_port.filter(|&port| port >= self.memory_size as usize) {
+            return Err(ConfigError::IoPortOutOfRange { port, memory_size: self.memory_size });
+        }
+        if self.load_offset >= self.memory_size as usize {
+            return Err(ConfigError::LoadOffsetOutOfRange { offset: self.load_offset, memory_size: self.memory_size });
+        }
+        if self.memory_init == MemoryInit::Pattern && self.memory_pattern.is_empty() {
+            return Err(ConfigError::EmptyMemoryPattern);
+        }
+        Ok(())
+    }
+
+    /// Positions of the initial population that hold a copy of `seed_program`, if any.
+    pub(crate) fn seed_slots(&self) -> Vec<usize> {
+        match self.seed_program {
+            Some(_) => (0..self.seed_copies).map(|copy| copy * self.population / self.seed_copies).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The subset of the config used by [`SUBLEQProgram::mutate`](crate::SUBLEQProgram::mutate).
+    pub fn mutation(&self) -> MutationConfig {
+        MutationConfig {
+            self_adaptation: self.self_adaptation,
+            indel_rate: self.indel_rate,
+            operator: self.mutation_operator,
+            block_rate: self.block_rate,
+            min_len: self.min_len,
+            max_len: self.max_len,
+            memory_size: self.memory_size,
+            rate_scale: 1.0,
+        }
+    }
+
+    /// The subset of the config the VM needs to execute a program.
+    pub fn vm(&self) -> VmConfig {
+        VmConfig {
+            memory_size: self.memory_size,
+            max_steps: self.max_steps,
+            halt_on_negative: self.halt_on_negative,
+            io_port: self.io_port,
+            input: self.input.clone(),
+            detect_cycles: self.detect_cycles,
+            isa: self.isa,
+            wrap_pc: self.wrap_pc,
+            restart_at_end: self.restart_at_end,
+            protect_code: self.protect_code,
+            load_offset: self.load_offset,
+            background: self.memory_init.background(self.memory_size, &self.memory_pattern, self.seed.unwrap_or(0)),
+        }
+    }
+}
+
+/// Builds a [`GaConfig`], starting from the defaults and checking invariants in [`build`](Self::build).
+#[derive(Clone, Debug)]
+pub struct GaConfigBuilder {
+    config: GaConfig,
+}
+
+impl GaConfigBuilder {
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.config.population = population;
+        self
+    }
+
+    pub fn generations(mut self, generations: usize) -> Self {
+        self.config.generations = generations;
+        self
+    }
+
+    pub fn stall_generations(mut self, stall_generations: usize) -> Self {
+        self.config.stall_generations = Some(stall_generations);
+        self
+    }
+
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.config.max_time = Some(max_time);
+        self
+    }
+
+    pub fn stall_trigger(mut self, stall_trigger: usize) -> Self {
+        self.config.stall_trigger = Some(stall_trigger);
+        self
+    }
+
+    pub fn hypermutation_factor(mut self, hypermutation_factor: f64) -> Self {
+        self.config.hypermutation_factor = hypermutation_factor;
+        self
+    }
+
+    pub fn hypermutation_generations(mut self, hypermutation_generations: usize) -> Self {
+        self.config.hypermutation_generations = hypermutation_generations;
+        self
+    }
+
+    pub fn restarts(mut self, restarts: usize) -> Self {
+        self.config.restarts = restarts;
+        self
+    }
+
+    pub fn initial_temp(mut self, initial_temp: f64) -> Self {
+        self.config.initial_temp = initial_temp;
+        self
+    }
+
+    pub fn cooling(mut self, cooling: f64) -> Self {
+        self.config.cooling = cooling;
+        self
+    }
+
+    pub fn find(mut self, find: usize) -> Self {
+        self.config.find = find;
+        self
+    }
+
+    pub fn find_distance(mut self, find_distance: usize) -> Self {
+        self.config.find_distance = find_distance;
+        self
+    }
+
+    pub fn verify_all(mut self, verify_all: bool) -> Self {
+        self.config.verify_all = verify_all;
+        self
+    }
+
+    pub fn mutation_rate(mut self, mutation_rate: f64) -> Self {
+        self.config.mutation_rate = mutation_rate;
+        self
+    }
+
+    pub fn self_adaptation(mut self, self_adaptation: f64) -> Self {
+        self.config.self_adaptation = self_adaptation;
+        self
+    }
+
+    pub fn adaptive_mutation(mut self, adaptive_mutation: AdaptiveMutation) -> Self {
+        self.config.adaptive_mutation = adaptive_mutation;
+        self
+    }
+
+    pub fn indel_rate(mut self, indel_rate: f64) -> Self {
+        self.config.indel_rate = indel_rate;
+        self
+    }
+
+    pub fn mutation_operator(mut self, operator: MutationOperator) -> Self {
+        self.config.mutation_operator = operator;
+        self
+    }
+
+    pub fn block_rate(mut self, block_rate: f64) -> Self {
+        self.config.block_rate = block_rate;
+        self
+    }
+
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.config.min_len = min_len;
+        self
+    }
+
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.config.max_len = max_len;
+        self
+    }
+
+    pub fn init_length_bias(mut self, bias: LengthBias) -> Self {
+        self.config.init_length_bias = bias;
+        self
+    }
+
+    pub fn memory_size(mut self, memory_size: i32) -> Self {
+        self.config.memory_size = memory_size;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.config.max_steps = max_steps;
+        self
+    }
+
+    pub fn halt_on_negative(mut self, halt_on_negative: bool) -> Self {
+        self.config.halt_on_negative = halt_on_negative;
+        self
+    }
+
+    pub fn io_port(mut self, io_port: usize) -> Self {
+        self.config.io_port = Some(io_port);
+        self
+    }
+
+    pub fn input(mut self, input: Vec<i32>) -> Self {
+        self.config.input = input;
+        self
+    }
+
+    pub fn detect_cycles(mut self, detect_cycles: bool) -> Self {
+        self.config.detect_cycles = detect_cycles;
+        self
+    }
+
+    pub fn isa(mut self, isa: InstructionSet) -> Self {
+        self.config.isa = isa;
+        self
+    }
+
+    pub fn wrap_pc(mut self, wrap_pc: bool) -> Self {
+        self.config.wrap_pc = wrap_pc;
+        self
+    }
+
+    pub fn restart_at_end(mut self, restart_at_end: bool) -> Self {
+        self.config.restart_at_end = restart_at_end;
+        self
+    }
+
+    pub fn protect_code(mut self, protect_code: bool) -> Self {
+        self.config.protect_code = protect_code;
+        self
+    }
+
+    pub fn load_offset(mut self, load_offset: usize) -> Self {
+        self.config.load_offset = load_offset;
+        self
+    }
+
+    pub fn memory_init(mut self, memory_init: MemoryInit) -> Self {
+        self.config.memory_init = memory_init;
+        self
+    }
+
+    pub fn memory_pattern(mut self, memory_pattern: Vec<i32>) -> Self {
+        self.config.memory_pattern = memory_pattern;
+        self
+    }
+
+    pub fn seed_program(mut self, program: SUBLEQProgram) -> Self {
+        self.config.seed_program = Some(program.code().to_vec());
+        self
+    }
+
+    pub fn seed_copies(mut self, seed_copies: usize) -> Self {
+        self.config.seed_copies = seed_copies;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn islands(mut self, islands: usize) -> Self {
+        self.config.islands = islands;
+        self
+    }
+
+    pub fn migrate_every(mut self, migrate_every: usize) -> Self {
+        self.config.migrate_every = migrate_every;
+        self
+    }
+
+    pub fn migrants(mut self, migrants: usize) -> Self {
+        self.config.migrants = migrants;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.config.selection = selection;
+        self
+    }
+
+    pub fn multiobjective(mut self, multiobjective: bool) -> Self {
+        self.config.multiobjective = multiobjective;
+        self
+    }
+
+    pub fn tournament_size(mut self, tournament_size: usize) -> Self {
+        self.config.tournament_size = tournament_size;
+        self
+    }
+
+    pub fn rank_pressure(mut self, rank_pressure: f64) -> Self {
+        self.config.rank_pressure = rank_pressure;
+        self
+    }
+
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.config.elitism = elitism;
+        self
+    }
+
+    pub fn immigrants(mut self, immigrants: usize) -> Self {
+        self.config.immigrants = immigrants;
+        self
+    }
+
+    pub fn gap(mut self, gap: f64) -> Self {
+        self.config.gap = gap;
+        self
+    }
+
+    pub fn replacement(mut self, replacement: Replacement) -> Self {
+        self.config.replacement = replacement;
+        self
+    }
+
+    pub fn crossover(mut self, crossover: Crossover) -> Self {
+        self.config.crossover = crossover;
+        self
+    }
+
+    pub fn no_crossover(mut self, no_crossover: bool) -> Self {
+        self.config.no_crossover = no_crossover;
+        self
+    }
+
+    pub fn parallel_breeding(mut self, parallel_breeding: bool) -> Self {
+        self.config.parallel_breeding = parallel_breeding;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    pub fn fitness(mut self, fitness: FitnessMode) -> Self {
+        self.config.fitness = fitness;
+        self
+    }
+
+    pub fn parsimony(mut self, parsimony: f64) -> Self {
+        self.config.parsimony = parsimony;
+        self
+    }
+
+    pub fn local_search(mut self, local_search: usize) -> Self {
+        self.config.local_search = local_search;
+        self
+    }
+
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.config.cache = cache;
+        self
+    }
+
+    pub fn cache_size(mut self, cache_size: usize) -> Self {
+        self.config.cache_size = Some(cache_size);
+        self
+    }
+
+    pub fn log_csv(mut self, path: PathBuf) -> Self {
+        self.config.log_csv = Some(path);
+        self
+    }
+
+    pub fn log_jsonl(mut self, path: PathBuf) -> Self {
+        self.config.log_jsonl = Some(path);
+        self
+    }
+
+    pub fn diversity_samples(mut self, diversity_samples: usize) -> Self {
+        self.config.diversity_samples = diversity_samples;
+        self
+    }
+
+    pub fn checkpoint_every(mut self, generations: usize) -> Self {
+        self.config.checkpoint_every = Some(generations);
+        self
+    }
+
+    pub fn checkpoint_file(mut self, path: PathBuf) -> Self {
+        self.config.checkpoint_file = path;
+        self
+    }
+
+    pub fn format(mut self, format: FileFormat) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    pub fn resume(mut self, path: PathBuf) -> Self {
+        self.config.resume = Some(path);
+        self
+    }
+
+    pub fn hall_of_fame(mut self, path: PathBuf) -> Self {
+        self.config.hall_of_fame = Some(path);
+        self
+    }
+
+    pub fn hall_of_fame_size(mut self, hall_of_fame_size: usize) -> Self {
+        self.config.hall_of_fame_size = hall_of_fame_size;
+        self
+    }
+
+    pub fn track_lineage(mut self, track_lineage: bool) -> Self {
+        self.config.track_lineage = track_lineage;
+        self
+    }
+
+    pub fn soup_size(mut self, soup_size: usize) -> Self {
+        self.config.soup_size = soup_size;
+        self
+    }
+
+    pub fn soup_slice(mut self, soup_slice: usize) -> Self {
+        self.config.soup_slice = soup_slice;
+        self
+    }
+
+    pub fn build(self) -> Result<GaConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Parses a positive duration written as a number with an optional unit: `ms`, `s`
+/// (the default), `m` or `h`, as in `500ms`, `30s`, `5m` or `1.5h`.
+pub fn parse_duration(text: &str) -> Result<Duration, ConfigError> {
+    let invalid = || ConfigError::Duration(text.to_string());
+    let number_end = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(number_end);
+    let seconds = match unit {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(invalid()),
+    };
+    let value: f64 = number.trim().parse().map_err(|_| invalid())?;
+    match Duration::try_from_secs_f64(value * seconds) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(invalid()),
+    }
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse_duration(&text).map(Some).map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    PopulationTooSmall { population: usize, min: usize },
+    EmptyTournament,
+    RankPressure(f64),
+    StallGenerations,
+    Duration(String),
+    Hypermutation,
+    HypermutationFactor(f64),
+    InitialTemp(f64),
+    Cooling(f64),
+    Islands { islands: usize, max: usize },
+    MigrationInterval,
+    Gap(f64),
+    MutationRate(f64),
+    SelfAdaptation(f64),
+    AdaptiveMutation,
+    Multiobjective,
+    Parsimony(f64),
+    IndelRate(f64),
+    BlockRate(f64),
+    LengthBounds { min_len: usize, max_len: usize },
+    MemoryTooSmall(i32),
+    ProgramExceedsMemory { max_len: usize, memory_size: i32 },
+    IoPortOutOfRange { port: usize, memory_size: i32 },
+    LoadOffsetOutOfRange { offset: usize, memory_size: i32 },
+    EmptyMemoryPattern,
+    SeedExceedsMemory { len: usize, memory_size: i32 },
+    SeedLength { len: usize, min_len: usize, max_len: usize },
+    SeedCopies { copies: usize, population: usize },
+    NoDiversitySamples,
+    CheckpointInterval,
+    EmptyHallOfFame,
+    NothingToFind,
+    EmptyCache,
+    BincodeUnavailable,
+    AsmCheckpoint,
+    SoupWindow { memory_size: i32, soup_size: usize },
+    SoupTooSmall { population: usize, max_len: usize, soup_size: usize },
+    CoevolutionArena { max_len: usize, memory_size: i32 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::PopulationTooSmall { population, min } => {
+                write!(f, "population ({}) must be at least {}", population, min)
+            }
+            ConfigError::EmptyTournament => write!(f, "tournament_size must be at least 1"),
+            ConfigError::RankPressure(pressure) => write!(f, "rank_pressure ({}) must be between 1 and 2", pressure),
+            ConfigError::StallGenerations => write!(f, "stall_generations must be at least 1"),
+            ConfigError::Duration(text) => {
+                write!(f, "invalid duration \"{}\": expected a positive number with an optional unit ms, s, m or h", text)
+            }
+            ConfigError::Hypermutation => write!(f, "stall_trigger and hypermutation_generations must be at least 1"),
+            ConfigError::HypermutationFactor(factor) => {
+                write!(f, "hypermutation_factor ({}) must be finite and positive", factor)
+            }
+            ConfigError::InitialTemp(temp) => write!(f, "initial_temp ({}) must be finite and positive", temp),
+            ConfigError::Cooling(cooling) => write!(f, "cooling ({}) must be in (0, 1]", cooling),
+            ConfigError::Islands { islands, max } => write!(f, "islands ({}) must be between 1 and {} for this population", islands, max),
+            ConfigError::MigrationInterval => write!(f, "migrate_every must be at least 1"),
+            ConfigError::Gap(gap) => write!(f, "gap ({}) must be in (0, 1]", gap),
+            ConfigError::MutationRate(rate) => write!(f, "mutation_rate ({}) must be between 0 and 1", rate),
+            ConfigError::SelfAdaptation(tau) => write!(f, "self_adaptation ({}) must not be negative", tau),
+            ConfigError::AdaptiveMutation => write!(f, "adaptive_mutation cannot be combined with self_adaptation"),
+            ConfigError::Multiobjective => {
+                write!(f, "multiobjective needs generational or steady-state mode, without adaptive_mutation or crowding replacement")
+            }
+            ConfigError::Parsimony(weight) => write!(f, "parsimony ({}) must be finite and not negative", weight),
+            ConfigError::IndelRate(rate) => write!(f, "indel_rate ({}) must be between 0 and 1", rate),
+            ConfigError::BlockRate(rate) => write!(f, "block_rate ({}) must be between 0 and 1", rate),
+            ConfigError::LengthBounds { min_len, max_len } => {
+                write!(f, "min_len ({}) must be at least 1 and no greater than max_len ({})", min_len, max_len)
+            }
+            ConfigError::MemoryTooSmall(memory_size) => {
+                write!(f, "memory_size ({}) must hold at least one instruction", memory_size)
+            }
+            ConfigError::ProgramExceedsMemory { max_len, memory_size } => {
+                write!(f, "max_len ({}) must not exceed memory_size ({})", max_len, memory_size)
+            }
+            ConfigError::IoPortOutOfRange { port, memory_size } => {
+                write!(f, "io_port ({}) must be an address below memory_size ({})", port, memory_size)
+            }
+            ConfigError::LoadOffsetOutOfRange { offset, memory_size } => {
+                write!(f, "load_offset ({}) must be an address below memory_size ({})", offset, memory_size)
+            }
+            ConfigError::EmptyMemoryPattern => write!(f, "memory_init pattern needs a non-empty memory_pattern"),
+            ConfigError::SeedExceedsMemory { len, memory_size } => {
+                write!(f, "seed program ({} words) must fit in memory_size ({})", len, memory_size)
+            }
+            ConfigError::SeedLength { len, min_len, max_len } => {
+                write!(f, "seed program ({} words) must be between min_len ({}) and max_len ({})", len, min_len, max_len)
+            }
+            ConfigError::SeedCopies { copies, population } => {
+                write!(f, "seed_copies ({}) must be between 1 and the population ({})", copies, population)
+            }
+            ConfigError::NoDiversitySamples => write!(f, "diversity_samples must be at least 1"),
+            ConfigError::CheckpointInterval => write!(f, "checkpoint_every must be at least 1"),
+            ConfigError::EmptyHallOfFame => write!(f, "hall_of_fame_size must be at least 1"),
+            ConfigError::NothingToFind => write!(f, "find must be at least 1"),
+            ConfigError::EmptyCache => write!(f, "cache_size must be at least 1"),
+            ConfigError::BincodeUnavailable => write!(f, "format bincode requires the bincode feature"),
+            ConfigError::AsmCheckpoint => write!(f, "checkpoints cannot be written in format asm"),
+            ConfigError::SoupWindow { memory_size, soup_size } => {
+                write!(f, "memory_size ({}) must not exceed soup_size ({})", memory_size, soup_size)
+            }
+            ConfigError::SoupTooSmall { population, max_len, soup_size } => write!(
+                f,
+                "soup_size ({}) cannot hold a population of {} programs of up to {} words",
+                soup_size, population, max_len
+            ),
+            ConfigError::CoevolutionArena { max_len, memory_size } => {
+                write!(f, "coevolution needs max_len ({}) of at most half of memory_size ({})", max_len, memory_size)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Read(_, e) => Some(e),
+            ConfigError::Parse(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{SUBLEQProgram, Word};
+
+/// How two parents are recombined into a child.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Crossover {
+    /// One cut anywhere in the shared prefix.
+    #[default]
+    Single,
+    /// Swap a run of whole instructions between two triplet-aligned cuts.
+    TwoPoint,
+    /// Take each gene of the shared prefix from either parent with equal probability.
+    Uniform,
+}
+
+impl Crossover {
+    pub fn apply<W: Word>(self, a: &SUBLEQProgram<W>, b: &SUBLEQProgram<W>, memory_size: i32, rng: &mut impl Rng) -> SUBLEQProgram<W> {
+        match self {
+            Crossover::Single => crossover(a, b, memory_size, rng),
+            Crossover::TwoPoint => crossover_two_point(a, b, memory_size, rng),
+            Crossover::Uniform => crossover_uniform(a, b, memory_size, rng),
+        }
+    }
+}
+
+pub fn crossover<W: Word>(a: &SUBLEQProgram<W>, b: &SUBLEQProgram<W>, memory_size: i32, rng: &mut impl Rng) -> SUBLEQProgram<W> {
+    let parents = (a, b);
+    let (a, b) = (a.code(), b.code());
+    let min_len = a.len().min(b.len());
+    let max_len = a.len().max(b.len());
+    // An empty parent leaves nothing to split, so the child is filled at random
+    let split = if min_len == 0 { 0 } else { rng.gen_range(0..min_len) };
+    let child_len = rng.gen_range(min_len..=max_len);
+
+    let mut child = vec![W::default(); child_len];
+
+    // Copy from first parent up to split point
+    child[..split].copy_from_slice(&a[..split]);
+
+    // Copy from second parent after split point, up to the minimum length
+    child[split..min_len].copy_from_slice(&b[split..min_len]);
+
+    fill_tail(&mut child, min_len, memory_size, rng);
+    offspring(child, parents)
+}
+
+/// Takes the child from `a`, except for the instructions between two cut points which
+/// come from `b`.
+///
+/// Both cuts fall on multiples of 3 within the shared prefix, so only whole
+/// instructions are exchanged; a trailing partial instruction stays with `a`.
+pub fn crossover_two_point<W: Word>(a: &SUBLEQProgram<W>, b: &SUBLEQProgram<W>, memory_size: i32, rng: &mut impl Rng) -> SUBLEQProgram<W> {
+    let parents = (a, b);
+    let (a, b) = (a.code(), b.code());
+    let min_len = a.len().min(b.len());
+    let max_len = a.len().max(b.len());
+    let instructions = min_len / 3;
+    let mut cuts = [rng.gen_range(0..=instructions) * 3, rng.gen_range(0..=instructions) * 3];
+    cuts.sort_unstable();
+    let child_len = rng.gen_range(min_len..=max_len);
+
+    let mut child = vec![W::default(); child_len];
+    child[..min_len].copy_from_slice(&a[..min_len]);
+    child[cuts[0]..cuts[1]].copy_from_slice(&b[cuts[0]..cuts[1]]);
+
+    fill_tail(&mut child, min_len, memory_size, rng);
+    offspring(child, parents)
+}
+
+/// Takes each gene of the shared prefix from `a` or `b` by a fair coin flip, so good
+/// instructions need not be contiguous to be combined.
+pub fn crossover_uniform<W: Word>(a: &SUBLEQProgram<W>, b: &SUBLEQProgram<W>, memory_size: i32, rng: &mut impl Rng) -> SUBLEQProgram<W> {
+    let parents = (a, b);
+    let (a, b) = (a.code(), b.code());
+    let min_len = a.len().min(b.len());
+    let max_len = a.len().max(b.len());
+    let child_len = rng.gen_range(min_len..=max_len);
+
+    let mut child = vec![W::default(); child_len];
+    for (i, gene) in child[..min_len].iter_mut().enumerate() {
+        *gene = if rng.gen_bool(0.5) { a[i] } else { b[i] };
+    }
+
+    fill_tail(&mut child, min_len, memory_size, rng);
+    offspring(child, parents)
+}
+
+/// A copy of `parent` as its own child, for breeding without crossover.
+pub(crate) fn clone_parent<W: Word>(parent: &SUBLEQProgram<W>) -> SUBLEQProgram<W> {
+    offspring(parent.code().to_vec(), (parent, parent))
+}
+
+// Children start from the mean of their parents' self-adapted rates, and remember
+// their parents if those are being tracked
+fn offspring<W: Word>(code: Vec<W>, (a, b): (&SUBLEQProgram<W>, &SUBLEQProgram<W>)) -> SUBLEQProgram<W> {
+    SUBLEQProgram::from_code(code).with_mutation_rate((a.mutation_rate() + b.mutation_rate()) / 2.0).with_parents(a, b)
+}
+
+// Genes past the shorter parent's length have no counterpart in both parents, so fill them randomly
+fn fill_tail<W: Word>(child: &mut [W], min_len: usize, memory_size: i32, rng: &mut impl Rng) {
+    for gene in child.iter_mut().skip(min_len) {
+        *gene = W::random(memory_size, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+    use crate::testing::{proptest_config, valid_code};
+    use crate::GaConfig;
+
+    fn operator() -> impl Strategy<Value = Crossover> {
+        prop_oneof![Just(Crossover::Single), Just(Crossover::TwoPoint), Just(Crossover::Uniform)]
+    }
+
+    proptest! {
+        #![proptest_config(proptest_config())]
+
+        #[test]
+        fn children_stay_in_bounds(a in valid_code(), b in valid_code(), operator in operator(), seed: u64) {
+            let (a, b) = (SUBLEQProgram::from_code(a), SUBLEQProgram::from_code(b));
+            let memory_size = GaConfig::default().memory_size;
+            let child = operator.apply(&a, &b, memory_size, &mut ChaCha12Rng::seed_from_u64(seed));
+            let (shorter, longer) = (a.code().len().min(b.code().len()), a.code().len().max(b.code().len()));
+            prop_assert!((shorter..=longer).contains(&child.code().len()));
+            prop_assert!(child.code().iter().all(|gene| (-memory_size..memory_size).contains(gene)));
+        }
+    }
+
+    #[test]
+    fn crosses_length_one_with_length_six() {
+        let (short, long) = (SUBLEQProgram::from_code(vec![7]), SUBLEQProgram::from_code(vec![1, 2, 3, 4, 5, 6]));
+        let mut rng = ChaCha12Rng::seed_from_u64(9);
+        for _ in 0..100 {
+            for (a, b) in [(&short, &long), (&long, &short)] {
+                let child = crossover(a, b, 256, &mut rng);
+                assert!((1..=6).contains(&child.code().len()));
+            }
+        }
+    }
+
+    #[test]
+    fn two_point_exchanges_whole_instructions() {
+        // Genes say which parent they came from; the 8-word parent ends in a partial instruction
+        let a = SUBLEQProgram::from_code(vec![1; 8]);
+        let b = SUBLEQProgram::from_code(vec![2; 13]);
+        let mut rng = ChaCha12Rng::seed_from_u64(10);
+        for _ in 0..200 {
+            let child = crossover_two_point(&a, &b, 256, &mut rng);
+            assert!((8..=13).contains(&child.code().len()));
+            let from_b: Vec<usize> = (0..8).filter(|&i| child.code()[i] == 2).collect();
+            if let (Some(&first), Some(&last)) = (from_b.first(), from_b.last()) {
+                assert_eq!(from_b.len(), last - first + 1, "b's genes form one run");
+                assert_eq!(first % 3, 0);
+                assert_eq!((last + 1) % 3, 0);
+            }
+            assert_eq!(child.code()[6..8], [1, 1], "the partial instruction stays with a");
+        }
+    }
+
+    #[test]
+    fn uniform_takes_each_shared_gene_from_a_parent() {
+        let a = SUBLEQProgram::from_code((1..=9).collect());
+        let b = SUBLEQProgram::from_code((1..=12).map(|gene| -gene).collect());
+        let mut rng = ChaCha12Rng::seed_from_u64(41);
+        let (mut from_a, mut from_b) = (0, 0);
+        for _ in 0..100 {
+            let child = crossover_uniform(&a, &b, 256, &mut rng);
+            assert!((9..=12).contains(&child.code().len()));
+            for (i, &gene) in child.code()[..9].iter().enumerate() {
+                assert!(gene == a.code()[i] || gene == b.code()[i], "gene {} came from neither parent", i);
+                if gene == a.code()[i] {
+                    from_a += 1;
+                } else {
+                    from_b += 1;
+                }
+            }
+        }
+        // 900 fair coin flips land well within these bounds
+        assert!((350..=550).contains(&from_a), "{} genes from a, {} from b", from_a, from_b);
+    }
+}
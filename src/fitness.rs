@@ -0,0 +1,281 @@
+use rand::RngCore;
+use serde::Deserialize;
+
+use crate::{ExecutionResult, SUBLEQProgram};
+
+/// An objective the GA maximizes, scored from a program's final machine state.
+///
+/// `result.memory` is a scratch buffer that is reused once `score` returns.
+pub trait Fitness: Send + Sync {
+    fn score(&self, result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> usize;
+
+    /// Scores an objective that makes random draws, taking them from `rng`. Each
+    /// generation [`run`](crate::run) gives every individual its own stream, derived from
+    /// the seed, the generation and its index, so scores are the same at any thread count.
+    /// Immigrants get streams of their own. Other scoring, such as by local search, uses
+    /// [`score`](Self::score), which this defaults to.
+    fn score_with_rng(&self, result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram, _rng: &mut dyn RngCore) -> usize {
+        self.score(result, program)
+    }
+
+    /// True if the program fully meets the objective, which ends [`run`](crate::run).
+    /// The default never stops early.
+    fn is_solution(&self, _result: &ExecutionResult<&mut [i32]>, _program: &SUBLEQProgram) -> bool {
+        false
+    }
+}
+
+/// What counts as progress towards self-replication.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitnessMode {
+    /// Longest run of genes matching the program at any offset.
+    #[default]
+    Prefix,
+    /// Number of non-overlapping full copies written after the original.
+    Copies,
+    /// Number of separate memory regions holding full copies after the original, each
+    /// worth more the farther it lies from the start of memory; only copies in at least
+    /// `SPREAD_REGIONS` regions count as a solution.
+    Spread,
+}
+
+/// Number of separate regions a program must copy itself into to solve
+/// [`FitnessMode::Spread`], since the run would otherwise end at its first copy.
+pub const SPREAD_REGIONS: usize = 2;
+
+/// Length of the longest prefix of `code` found at any offset in `memory[from..]`.
+pub fn longest_prefix_match(memory: &[i32], code: &[i32], from: usize) -> usize {
+    longest_prefix(memory, code, from).map_or(0, |(length, _)| length)
+}
+
+/// Length and earliest offset of the longest prefix [`longest_prefix_match`] finds, or
+/// `None` if not even the first word matches anywhere.
+pub(crate) fn longest_prefix(memory: &[i32], code: &[i32], from: usize) -> Option<(usize, usize)> {
+    let mut longest = None;
+    // A program longer than memory fits at no offset, rather than underflowing
+    let last = memory.len().checked_sub(code.len())?;
+    for i in from..=last {
+        let mut correct = 0;
+        for (j, &val) in code.iter().enumerate() {
+            if memory[i + j] == val {
+                correct += 1;
+            } else {
+                break;
+            }
+        }
+        if correct > longest.map_or(0, |(length, _)| length) {
+            longest = Some((correct, i));
+        }
+    }
+    longest
+}
+
+/// Number of non-overlapping full copies of `code` in `memory[from..]`, scanning left to right.
+pub fn count_copies(memory: &[i32], code: &[i32], from: usize) -> usize {
+    if code.is_empty() {
+        return 0;
+    }
+    let mut copies = 0;
+    let mut i = from;
+    while i + code.len() <= memory.len() {
+        if memory[i..i + code.len()] == *code {
+            copies += 1;
+            i += code.len();
+        } else {
+            i += 1;
+        }
+    }
+    copies
+}
+
+/// Start offsets of the non-overlapping full copies [`count_copies`] counts.
+pub fn copy_offsets(memory: &[i32], code: &[i32], from: usize) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    if code.is_empty() {
+        return offsets;
+    }
+    let mut i = from;
+    while i + code.len() <= memory.len() {
+        if memory[i..i + code.len()] == *code {
+            offsets.push(i);
+            i += code.len();
+        } else {
+            i += 1;
+        }
+    }
+    offsets
+}
+
+/// True if a full copy of `code` starts anywhere in `memory` but address 0, where the
+/// program was loaded. Unlike the copies [`count_copies`] finds, it may overlap the
+/// original.
+pub fn has_full_copy(memory: &[i32], code: &[i32]) -> bool {
+    !code.is_empty() && memory.windows(code.len()).skip(1).any(|window| window == code)
+}
+
+/// Start offsets of the separate regions of `memory[from..]` holding full copies of
+/// `code`, found as by [`count_copies`]; copies written back to back form one region.
+pub fn copy_regions(memory: &[i32], code: &[i32], from: usize) -> Vec<usize> {
+    let mut regions = Vec::new();
+    if code.is_empty() {
+        return regions;
+    }
+    let mut i = from;
+    let mut region_end = None;
+    while i + code.len() <= memory.len() {
+        if memory[i..i + code.len()] == *code {
+            if region_end != Some(i) {
+                regions.push(i);
+            }
+            i += code.len();
+            region_end = Some(i);
+        } else {
+            i += 1;
+        }
+    }
+    regions
+}
+
+/// Rewards programs for copying themselves, as measured by `mode`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReplicationFitness {
+    pub mode: FitnessMode,
+    /// Score subtracted per word of code from programs that have not made a full
+    /// copy, which are otherwise not rewarded for being short.
+    pub parsimony: f64,
+}
+
+impl ReplicationFitness {
+    pub fn new(mode: FitnessMode) -> Self {
+        ReplicationFitness { mode, parsimony: 0.0 }
+    }
+
+    pub fn with_parsimony(mut self, parsimony: f64) -> Self {
+        self.parsimony = parsimony;
+        self
+    }
+}
+
+impl Fitness for ReplicationFitness {
+    fn score(&self, result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> usize {
+        let (memory, code) = (&*result.memory, program.code());
+        // Reward full copies, shorter programs, and fewer execution steps
+        let efficiency = |copies: usize| copies * 1000 / (code.len() * result.steps.max(1)).max(1);
+        let partial = |matched: usize| matched.saturating_sub((self.parsimony * code.len() as f64) as usize);
+        match self.mode {
+            FitnessMode::Prefix => {
+                let max_copies = longest_prefix_match(memory, code, 0);
+                if max_copies == code.len() {
+                    efficiency(max_copies)
+                } else {
+                    partial(max_copies)
+                }
+            }
+            // Only memory past the original counts, as in is_solution
+            FitnessMode::Copies => match count_copies(memory, code, code.len()) {
+                0 => partial(longest_prefix_match(memory, code, code.len())),
+                copies => efficiency(copies * code.len()),
+            },
+            FitnessMode::Spread => {
+                let regions = copy_regions(memory, code, code.len());
+                if regions.is_empty() {
+                    return partial(longest_prefix_match(memory, code, code.len()));
+                }
+                // A region counts as one copy at the original, up to two at the end of memory
+                efficiency(regions.iter().map(|&start| code.len() + code.len() * start / memory.len()).sum())
+            }
+        }
+    }
+
+    /// A full copy of the program appears in memory after the original, or in spread
+    /// mode copies appear in [`SPREAD_REGIONS`] separate regions.
+    fn is_solution(&self, result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> bool {
+        match self.mode {
+            FitnessMode::Spread => copy_regions(result.memory, program.code(), program.code().len()).len() >= SPREAD_REGIONS,
+            _ => count_copies(result.memory, program.code(), program.code().len()) > 0,
+        }
+    }
+}
+
+/// Rewards final memory for matching `target` cell by cell, starting at address `offset`.
+#[derive(Clone, Debug)]
+pub struct HammingTargetFitness {
+    pub target: Vec<i32>,
+    pub offset: usize,
+}
+
+impl HammingTargetFitness {
+    pub fn new(target: Vec<i32>, offset: usize) -> Self {
+        HammingTargetFitness { target, offset }
+    }
+}
+
+impl Fitness for HammingTargetFitness {
+    /// Number of target cells matched; cells past the end of memory never match.
+    fn score(&self, result: &ExecutionResult<&mut [i32]>, _program: &SUBLEQProgram) -> usize {
+        let memory = result.memory.get(self.offset..).unwrap_or_default();
+        memory.iter().zip(&self.target).filter(|(cell, target)| cell == target).count()
+    }
+
+    fn is_solution(&self, result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> bool {
+        self.score(result, program) == self.target.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GaConfig;
+
+    #[test]
+    fn finds_a_copy_ending_at_the_end_of_memory() {
+        let code = [5, -3, 7];
+        let memory = [5, -3, 7, 0, 0, 5, -3, 7];
+        assert_eq!(longest_prefix(&memory, &code, code.len()), Some((3, 5)));
+        assert_eq!(count_copies(&memory, &code, code.len()), 1);
+    }
+
+    #[test]
+    fn scans_programs_as_long_as_memory() {
+        let code: Vec<i32> = (1..=12).collect();
+        assert_eq!(longest_prefix_match(&code, &code, 0), 12);
+        assert_eq!(longest_prefix_match(&code, &code, 1), 0);
+        // One cell more than the program leaves a single offset past address 0
+        let mut memory = vec![0];
+        memory.extend(&code);
+        assert_eq!(longest_prefix_match(&memory, &code, 1), 12);
+        assert_eq!(longest_prefix_match(&memory, &code[..11], 1), 11);
+        // Longer than memory fits nowhere
+        assert_eq!(longest_prefix_match(&code[..11], &code, 0), 0);
+        assert_eq!(count_copies(&code[..11], &code, 0), 0);
+    }
+
+    #[test]
+    fn scores_programs_filling_memory() {
+        for (length, memory_size) in [(12, 12), (11, 12), (10, 12)] {
+            let vm = GaConfig { memory_size, ..GaConfig::default() }.vm();
+            let program = SUBLEQProgram::from_code((0..length).map(|i| i % memory_size).collect());
+            assert!(!program.verify_replication(&vm));
+            // The only copy sits at the last offset, which leaves no room after the original
+            let mut memory = vec![0; memory_size as usize];
+            let last = memory.len() - program.code().len();
+            memory[last..].copy_from_slice(program.code());
+            let result = ExecutionResult {
+                memory: &mut memory[..],
+                steps: 1,
+                halted: true,
+                writes: 0,
+                self_modified: false,
+                self_mod_writes: 0,
+                output: Vec::new(),
+                cycled: false,
+            };
+            for (mode, expected) in [(FitnessMode::Prefix, 1000), (FitnessMode::Copies, 0), (FitnessMode::Spread, 0)] {
+                let fitness = ReplicationFitness::new(mode);
+                assert_eq!(fitness.score(&result, &program), expected, "{:?} with length {}", mode, length);
+                assert!(!fitness.is_solution(&result, &program));
+            }
+        }
+    }
+}
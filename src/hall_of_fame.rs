@@ -0,0 +1,82 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::stats::rank_key;
+use crate::SUBLEQProgram;
+
+/// The best distinct genomes seen over a whole run, fittest first, with equally fit
+/// genomes ordered as by the run's best-program tie-break, and every solution the run
+/// discovered, which may rank below them.
+#[derive(Clone, Serialize)]
+pub struct HallOfFame {
+    capacity: usize,
+    entries: Vec<HallOfFameEntry>,
+    solutions: Vec<HallOfFameEntry>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct HallOfFameEntry {
+    pub fitness: usize,
+    /// Generation in which the genome was first admitted.
+    pub generation: usize,
+    #[serde(flatten)]
+    pub program: SUBLEQProgram,
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> Self {
+        HallOfFame { capacity, entries: Vec::with_capacity(capacity + 1), solutions: Vec::new() }
+    }
+
+    pub fn entries(&self) -> &[HallOfFameEntry] {
+        &self.entries
+    }
+
+    /// Discovered solutions in the order they were found; see [`add_solution`](Self::add_solution).
+    pub fn solutions(&self) -> &[HallOfFameEntry] {
+        &self.solutions
+    }
+
+    /// Records a solution found in `generation`, kept whatever its rank and apart from
+    /// the top entries.
+    pub fn add_solution(&mut self, generation: usize, program: &SUBLEQProgram, fitness: usize) {
+        self.solutions.push(HallOfFameEntry { fitness, generation, program: program.clone() });
+    }
+
+    /// Admits every program that ranks ahead of the current worst entry and is not already
+    /// present, so the contents do not depend on the order of the population.
+    ///
+    /// A genome already present keeps its entry, so a long-lived genome holds its
+    /// original generation.
+    pub fn update(&mut self, generation: usize, population: &[SUBLEQProgram], fitness_scores: &[usize]) {
+        for (program, &fitness) in population.iter().zip(fitness_scores) {
+            let key = rank_key(program, fitness);
+            if self.entries.len() == self.capacity && self.entries.last().is_none_or(|worst| key >= rank_key(&worst.program, worst.fitness)) {
+                continue;
+            }
+            if self.entries.iter().any(|entry| entry.program.code() == program.code()) {
+                continue;
+            }
+            let at = self.entries.partition_point(|entry| rank_key(&entry.program, entry.fitness) < key);
+            self.entries.insert(at, HallOfFameEntry { fitness, generation, program: program.clone() });
+            self.entries.truncate(self.capacity);
+        }
+    }
+
+    /// Writes the entries and solutions as JSON, along with the memory size their fitness
+    /// was measured in.
+    pub fn save(&self, path: &Path, memory_size: i32) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Saved<'a> {
+            memory_size: i32,
+            entries: &'a [HallOfFameEntry],
+            solutions: &'a [HallOfFameEntry],
+        }
+        let saved = Saved { memory_size, entries: &self.entries, solutions: &self.solutions };
+        let json = serde_json::to_string_pretty(&saved).expect("hall of fame always serializes");
+        fs::write(path, json + "\n")
+    }
+}
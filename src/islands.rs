@@ -0,0 +1,46 @@
+use std::ops::Range;
+
+use crate::SUBLEQProgram;
+
+/// Splits a population of `population` individuals into `islands` contiguous ranges
+/// whose sizes differ by at most one.
+pub(crate) fn island_ranges(population: usize, islands: usize) -> Vec<Range<usize>> {
+    let (size, extra) = (population / islands, population % islands);
+    let mut start = 0;
+    (0..islands)
+        .map(|i| {
+            let end = start + size + usize::from(i < extra);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect()
+}
+
+/// Copies the `migrants` fittest individuals of each island over the least fit of the
+/// next island in the ring, scores included.
+///
+/// Every island sends the emigrants it had before any arrivals, and at least one
+/// native per island survives.
+pub(crate) fn migrate(population: &mut [SUBLEQProgram], scores: &mut [usize], islands: &[Range<usize>], migrants: usize) {
+    let ranked = |range: &Range<usize>, scores: &[usize]| {
+        let mut ranked: Vec<usize> = range.clone().collect();
+        ranked.sort_by(|&i, &j| scores[j].cmp(&scores[i]));
+        ranked
+    };
+    let emigrants: Vec<Vec<(SUBLEQProgram, usize)>> = islands
+        .iter()
+        .map(|range| {
+            let count = migrants.min(range.len().saturating_sub(1));
+            ranked(range, scores)[..count].iter().map(|&i| (population[i].clone(), scores[i])).collect()
+        })
+        .collect();
+    for (from, emigrants) in emigrants.into_iter().enumerate() {
+        let to = &islands[(from + 1) % islands.len()];
+        let worst = ranked(to, scores);
+        for (&slot, (program, score)) in worst.iter().rev().zip(emigrants) {
+            population[slot] = program;
+            scores[slot] = score;
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use rand::Rng;
+use serde::Deserialize;
+
+/// Which lengths within `[min_len, max_len]` the initial population favours.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LengthBias {
+    /// Lengths are triangularly distributed, most likely at `min_len` and least likely
+    /// at `max_len`, since short programs replicate far more often.
+    Short,
+    /// Every length is equally likely.
+    #[default]
+    Uniform,
+    /// Lengths are triangularly distributed the other way, most likely at `max_len`.
+    Long,
+}
+
+impl LengthBias {
+    /// A random program length in `min_len..=max_len`.
+    pub fn sample(self, min_len: usize, max_len: usize, rng: &mut impl Rng) -> usize {
+        let mut draw = || rng.gen_range(min_len..=max_len);
+        // The smaller (or larger) of two uniform draws has a linearly sloped distribution
+        match self {
+            LengthBias::Short => draw().min(draw()),
+            LengthBias::Uniform => draw(),
+            LengthBias::Long => draw().max(draw()),
+        }
+    }
+}
@@ -0,0 +1,1463 @@
+mod adaptive;
+mod asm;
+#[cfg(feature = "bincode")]
+mod binary;
+mod cache;
+mod canonical;
+mod checkpoint;
+mod cluster;
+mod coevolution;
+mod config;
+mod crossover;
+mod fitness;
+mod hall_of_fame;
+mod islands;
+mod length_bias;
+mod lineage;
+mod memory_init;
+mod minimize;
+mod mutation;
+mod par;
+mod pareto;
+mod replacement;
+mod replication;
+mod save;
+mod selection;
+mod soup;
+mod stats;
+#[cfg(test)]
+mod testing;
+mod vm;
+mod word;
+#[cfg(feature = "pyo3")]
+mod python;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+
+use log::{debug, info};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand_distr::StandardNormal;
+use adaptive::OneFifthRule;
+use checkpoint::Progress;
+use par::*;
+use replacement::most_similar;
+use serde::{Deserialize, Serialize};
+use stats::{best_index, CsvLog, JsonlLog};
+// std::time::Instant panics on wasm32-unknown-unknown
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+pub use adaptive::{AdaptiveMutation, ONE_FIFTH_FACTOR, ONE_FIFTH_WINDOW};
+pub use asm::{assemble, disassemble, AssembleError};
+#[cfg(feature = "bincode")]
+pub use binary::{CHECKPOINT_MAGIC, PROGRAM_BIN_VERSION, PROGRAM_MAGIC};
+pub use cache::FitnessCache;
+pub use canonical::canonicalize;
+pub use checkpoint::CHECKPOINT_VERSION;
+pub use cluster::{cluster, Cluster};
+pub use coevolution::{encounter, parasite_offset, Coevolution, Encounter};
+pub use config::{parse_duration, ConfigError, GaConfig, GaConfigBuilder, Mode};
+pub use crossover::{crossover, crossover_two_point, crossover_uniform, Crossover};
+pub use fitness::{copy_offsets, copy_regions, count_copies, has_full_copy, longest_prefix_match, Fitness, FitnessMode, SPREAD_REGIONS, HammingTargetFitness, ReplicationFitness};
+pub use hall_of_fame::{HallOfFame, HallOfFameEntry};
+pub use length_bias::LengthBias;
+pub use memory_init::MemoryInit;
+pub use lineage::{Lineage, LineageRecord};
+pub use minimize::minimize;
+pub use mutation::{MutationOperator, MAX_BLOCK_INSTRUCTIONS};
+pub use pareto::{crowding_distances, non_dominated_ranks, Objectives};
+pub use replacement::Replacement;
+pub use replication::ReplicationReport;
+pub use save::{FileFormat, ProgramFileError};
+pub use selection::{rank_probabilities, roulette_select, tournament_select, RankWheel, RouletteWheel, Selection, Selector};
+pub use soup::{Organism, Soup, SoupStats};
+pub use stats::{diversity, gene_stats, population_entropy, program_distance, save_gene_stats, GeneStats, GenerationStats, RunSummary, ENTROPY_BUCKETS};
+pub use vm::{ExecError, ExecutionResult, InstructionSet, Machine, StepRecord, VmConfig};
+pub use word::Word;
+
+/// Mutation rate given to programs that were not assigned one explicitly.
+pub const DEFAULT_MUTATION_RATE: f64 = 0.05;
+
+/// Bounds a self-adapted mutation rate is kept within.
+pub const MUTATION_RATE_RANGE: (f64, f64) = (0.001, 0.5);
+
+/// Parameters of [`SUBLEQProgram::mutate`].
+#[derive(Clone, Copy, Debug)]
+pub struct MutationConfig {
+    /// Learning rate of the lognormal self-adaptation; 0 keeps each program's rate fixed.
+    pub self_adaptation: f64,
+    pub indel_rate: f64,
+    pub operator: MutationOperator,
+    pub block_rate: f64,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub memory_size: i32,
+    /// Factor applied to every program's rate when point-mutating, raised above 1
+    /// during hypermutation and tuned by [`AdaptiveMutation::OneFifth`].
+    pub rate_scale: f64,
+}
+
+/// A candidate program: a flat list of SUBLEQ words, three per instruction.
+///
+/// With lineage tracking each program also has a unique nonzero id and, unless it
+/// belongs to the initial population, the ids of its two parents.
+///
+/// Words are `i32` unless another [`Word`] type is chosen; the GA itself, fitness
+/// functions and program files work on `i32` programs.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SUBLEQProgram<W: Word = i32> {
+    code: Vec<W>,
+    #[serde(default = "default_mutation_rate")]
+    mutation_rate: f64,
+    #[serde(default, skip_serializing_if = "is_untracked")]
+    id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parents: Option<(u64, u64)>,
+}
+
+fn default_mutation_rate() -> f64 {
+    DEFAULT_MUTATION_RATE
+}
+
+fn is_untracked(id: &u64) -> bool {
+    *id == 0
+}
+
+impl<W: Word> SUBLEQProgram<W> {
+    pub fn new(length: usize, memory_size: i32, rng: &mut impl Rng) -> Self {
+        SUBLEQProgram {
+            code: (0..length).map(|_| W::random(memory_size, rng)).collect(),
+            mutation_rate: DEFAULT_MUTATION_RATE,
+            id: 0,
+            parents: None,
+        }
+    }
+
+    pub fn from_code(code: Vec<W>) -> Self {
+        SUBLEQProgram { code, mutation_rate: DEFAULT_MUTATION_RATE, id: 0, parents: None }
+    }
+
+    pub fn with_mutation_rate(mut self, mutation_rate: f64) -> Self {
+        self.mutation_rate = mutation_rate;
+        self
+    }
+
+    /// Records `a` and `b` as the parents, if both are tracked.
+    pub(crate) fn with_parents(mut self, a: &SUBLEQProgram<W>, b: &SUBLEQProgram<W>) -> Self {
+        if a.id != 0 && b.id != 0 {
+            self.parents = Some((a.id, b.id));
+        }
+        self
+    }
+
+    /// Lineage id, or 0 if the program is not tracked.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn parents(&self) -> Option<(u64, u64)> {
+        self.parents
+    }
+
+    pub fn code(&self) -> &[W] {
+        &self.code
+    }
+
+    /// Per-gene probability of replacement this program passes to `mutate`.
+    pub fn mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    /// Point-mutates genes at the program's own rate and possibly replaces a block of
+    /// instructions, as `params.operator` selects, then possibly inserts or deletes one
+    /// whole instruction. Returns true if a block mutation happened.
+    ///
+    /// With self-adaptation enabled the rate is first scaled by a lognormal factor and
+    /// clamped to [`MUTATION_RATE_RANGE`]. Insertions and deletions happen at triplet
+    /// boundaries and only when the result stays within `[min_len, max_len]`.
+    pub fn mutate(&mut self, params: &MutationConfig, rng: &mut impl Rng) -> bool {
+        if params.self_adaptation > 0.0 {
+            let factor = (params.self_adaptation * rng.sample::<f64, _>(StandardNormal)).exp();
+            self.mutation_rate = (self.mutation_rate * factor).clamp(MUTATION_RATE_RANGE.0, MUTATION_RATE_RANGE.1);
+        }
+
+        if params.operator.point() {
+            for gene in &mut self.code {
+                if rng.gen::<f64>() < self.mutation_rate * params.rate_scale {
+                    *gene = W::random(params.memory_size, rng);
+                }
+            }
+        }
+
+        let block = params.operator.block()
+            && rng.gen::<f64>() < params.block_rate
+            && mutation::block_mutate(&mut self.code, params.memory_size, rng);
+
+        if params.indel_rate > 0.0 && rng.gen::<f64>() < params.indel_rate {
+            let instructions = self.code.len() / 3;
+            if rng.gen_bool(0.5) {
+                if self.code.len() + 3 <= params.max_len {
+                    let at = rng.gen_range(0..=instructions) * 3;
+                    let triplet: Vec<W> = (0..3).map(|_| W::random(params.memory_size, rng)).collect();
+                    self.code.splice(at..at, triplet);
+                }
+            } else if instructions > 0 && self.code.len() >= params.min_len + 3 {
+                let at = rng.gen_range(0..instructions) * 3;
+                self.code.drain(at..at + 3);
+            }
+        }
+        block
+    }
+
+    /// Runs the program in a fresh memory.
+    pub fn execute(&self, vm: &VmConfig) -> ExecutionResult<Vec<W>, W> {
+        vm::execute(&self.code, vm, vec![W::default(); vm.memory_size as usize])
+    }
+
+    /// Like [`execute`](Self::execute), but returns an [`ExecError`] for executions that
+    /// did not halt cleanly: ones that hit `max_steps` or a cycle, wrote nothing, or
+    /// fell off the end of memory.
+    pub fn execute_checked(&self, vm: &VmConfig) -> Result<ExecutionResult<Vec<W>, W>, ExecError<W>> {
+        vm::execute_checked(&self.code, vm)
+    }
+
+    /// Runs the program in `memory`, a reusable buffer of `memory_size` cells, instead
+    /// of allocating one. The buffer is cleared first; the final memory is left in it.
+    pub fn execute_into<'m>(&self, vm: &VmConfig, memory: &'m mut [W]) -> ExecutionResult<&'m mut [W], W> {
+        vm::execute(&self.code, vm, memory)
+    }
+
+    /// Runs the program like [`execute`](Self::execute), also recording every instruction executed.
+    pub fn execute_trace(&self, vm: &VmConfig) -> (Vec<StepRecord<W>>, ExecutionResult<Vec<W>, W>) {
+        let mut machine = Machine::new(&self.code, vm);
+        let trace = machine.by_ref().collect();
+        (trace, machine.finish())
+    }
+}
+
+impl SUBLEQProgram {
+    /// Runs the program in this thread's scratch memory and hands the result to `f`.
+    fn with_execution<R>(&self, vm: &VmConfig, f: impl FnOnce(ExecutionResult<&mut [i32]>) -> R) -> R {
+        thread_local! {
+            static SCRATCH: RefCell<Vec<i32>> = const { RefCell::new(Vec::new()) };
+        }
+        SCRATCH.with(|scratch| {
+            let mut scratch = scratch.borrow_mut();
+            scratch.resize(vm.memory_size as usize, 0);
+            f(self.execute_into(vm, &mut scratch))
+        })
+    }
+
+    /// Score under the built-in replication objective; see [`ReplicationFitness`].
+    pub fn fitness(&self, vm: &VmConfig, mode: FitnessMode) -> usize {
+        self.evaluate(vm, &ReplicationFitness::new(mode))
+    }
+
+    /// Runs the program and scores the result with `fitness`.
+    pub fn evaluate(&self, vm: &VmConfig, fitness: &dyn Fitness) -> usize {
+        self.with_execution(vm, |result| fitness.score(&result, self))
+    }
+
+    /// [`evaluate`](Self::evaluate) with [`score_with_rng`](Fitness::score_with_rng),
+    /// also checking from the same execution whether the program wrote a full copy of
+    /// itself and, if `verify`, whether it solves `fitness`.
+    pub(crate) fn assess(&self, vm: &VmConfig, fitness: &dyn Fitness, rng: &mut dyn RngCore, verify: bool) -> Evaluation {
+        self.with_execution(vm, |result| Evaluation {
+            score: fitness.score_with_rng(&result, self, rng),
+            copied: has_full_copy(result.memory, self.code()),
+            solved: verify && fitness.is_solution(&result, self),
+        })
+    }
+
+    /// Runs the program and asks `fitness` whether it fully meets the objective.
+    pub fn solves(&self, vm: &VmConfig, fitness: &dyn Fitness) -> bool {
+        self.with_execution(vm, |result| fitness.is_solution(&result, self))
+    }
+
+    /// Like [`solves`](Self::solves), returning the execution that met the objective.
+    pub fn solution(&self, vm: &VmConfig, fitness: &dyn Fitness) -> Option<ExecutionResult> {
+        self.with_execution(vm, |result| fitness.is_solution(&result, self).then(|| result.into_owned()))
+    }
+
+    /// True if a full copy of the program appears in memory after the original.
+    pub fn verify_replication(&self, vm: &VmConfig) -> bool {
+        self.solves(vm, &ReplicationFitness::default())
+    }
+}
+
+/// What one scoring execution of a program found; see [`SUBLEQProgram::assess`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Evaluation {
+    pub(crate) score: usize,
+    /// The program wrote a full copy of itself anywhere, as found by [`has_full_copy`].
+    pub(crate) copied: bool,
+    /// The program solves the objective; only checked when asked to.
+    pub(crate) solved: bool,
+}
+
+/// Why [`run`] stopped evolving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// A program met the fitness objective, e.g. a self-replicator was verified.
+    Replicated,
+    /// The best fitness did not improve for `stall_generations` generations.
+    Stalled,
+    /// The generation limit was reached.
+    GenerationLimit,
+    /// The `max_time` budget ran out before the next generation started.
+    TimeLimit,
+    /// The callback given to [`run_with_callback`] asked to stop.
+    Interrupted,
+}
+
+/// A program that met the fitness objective during a run.
+#[derive(Clone)]
+pub struct Discovery {
+    pub generation: usize,
+    pub program: SUBLEQProgram,
+    /// Final machine state from the execution that verified it.
+    pub result: ExecutionResult,
+}
+
+/// What [`run`] found.
+pub struct RunReport {
+    pub stop: StopReason,
+    /// The first program to meet the fitness objective, if any did.
+    pub replicator: Option<SUBLEQProgram>,
+    /// Final machine state of `replicator`, from the execution that verified it.
+    pub replicator_result: Option<ExecutionResult>,
+    /// Every distinct solution found, in order, up to `find` of them.
+    pub discoveries: Vec<Discovery>,
+    /// The fittest program seen in any generation, the earliest one on ties; `None` if
+    /// no generation was evaluated.
+    pub best: Option<SUBLEQProgram>,
+    pub best_fitness: usize,
+    /// Generation in which the best fitness was last improved.
+    pub last_improvement: usize,
+    /// The last generation scored, empty if none was.
+    pub population: Vec<SUBLEQProgram>,
+    /// Fitness of each program in `population`.
+    pub fitness_scores: Vec<usize>,
+    /// Parentage of every individual, if `track_lineage` was set.
+    pub lineage: Option<Lineage>,
+    /// One program per distinct trade-off on the Pareto front of `population`, most
+    /// copied first, if `multiobjective` was set.
+    pub pareto_front: Vec<(SUBLEQProgram, Objectives)>,
+    pub summary: RunSummary,
+}
+
+/// Evolves a population under `config`, scoring it with `fitness`, until a program
+/// meets the objective or the generation limit is reached.
+///
+/// [`ReplicationFitness::new`]`(config.fitness)` searches for self-replicators.
+///
+/// With `resume` set, the population and random state are restored from a checkpoint
+/// instead, and the run continues as the checkpointed run would have.
+pub fn run(config: GaConfig, fitness: Box<dyn Fitness>) -> Result<RunReport, RunError> {
+    run_with_callback(config, fitness, |_| ControlFlow::Continue(()))
+}
+
+/// Like [`run`], handing the statistics of each generation to `callback` once it has
+/// been scored. Returning [`ControlFlow::Break`] ends the run after that generation,
+/// unless it produced a solution.
+pub fn run_with_callback(
+    config: GaConfig,
+    fitness: Box<dyn Fitness>,
+    mut callback: impl FnMut(GenerationStats) -> ControlFlow<()>,
+) -> Result<RunReport, RunError> {
+    config.validate().map_err(RunError::Config)?;
+    let started = Instant::now();
+    let vm = config.vm();
+    let random_program = |rng: &mut ChaCha12Rng| {
+        let length = config.init_length_bias.sample(config.min_len, config.max_len, rng);
+        SUBLEQProgram::new(length, config.memory_size, rng).with_mutation_rate(config.mutation_rate)
+    };
+    let (start, mut population, progress, mut rng) = match &config.resume {
+        Some(path) => {
+            let checkpoint = checkpoint::load(path, config.memory_size)?;
+            if checkpoint.population.len() < 2 * config.islands {
+                return Err(RunError::CheckpointTooSmall { path: path.clone(), len: checkpoint.population.len(), islands: config.islands });
+            }
+            debug!("Resuming {} at generation {}", path.display(), checkpoint.generation);
+            (checkpoint.generation, checkpoint.population, checkpoint.progress, checkpoint.rng)
+        }
+        None => {
+            let mut rng = match config.seed {
+                Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+                None => ChaCha12Rng::from_entropy(),
+            };
+            let mut population: Vec<SUBLEQProgram> = (0..config.population).map(|_| random_program(&mut rng)).collect();
+            if let Some(code) = &config.seed_program {
+                for slot in config.seed_slots() {
+                    population[slot] = SUBLEQProgram::from_code(code.clone()).with_mutation_rate(config.mutation_rate);
+                }
+            }
+            (0, population, Progress { best: None, last_improvement: 0, restarted_at: 0, first_full_copy: None }, rng)
+        }
+    };
+    let mut cache = config.cache.then(|| FitnessCache::new(config.cache_size));
+    // Scoring streams come from the main generator's seed rather than its draws, so
+    // deterministic objectives leave the run unchanged
+    let run_seed = u64::from_le_bytes(rng.get_seed()[..8].try_into().unwrap());
+    let mut log = match &config.log_csv {
+        Some(path) => Some(CsvLog::create(path).map_err(|e| RunError::Log(path.clone(), e))?),
+        None => None,
+    };
+    let mut jsonl = match &config.log_jsonl {
+        Some(path) => Some(JsonlLog::create(path).map_err(|e| RunError::Log(path.clone(), e))?),
+        None => None,
+    };
+    let mut lineage = config.track_lineage.then(|| Lineage::new(&mut population, start));
+    let mut hall_of_fame = config.hall_of_fame.is_some().then(|| HallOfFame::new(config.hall_of_fame_size));
+
+    let islands = islands::island_ranges(population.len(), config.islands);
+    let (mut hypermutation_until, mut stall_reference) = (None, start);
+    #[cfg(feature = "parallel")]
+    let pool = match config.threads {
+        0 => None,
+        threads => Some(ThreadPoolBuilder::new().num_threads(threads).build().map_err(RunError::ThreadPool)?),
+    };
+    #[cfg(not(feature = "parallel"))]
+    let pool: Option<ThreadPool> = None;
+    // Scores that survivors of a steady-state step carry into the next iteration
+    let mut carried: Vec<Option<usize>> = vec![None; population.len()];
+    // Score of each child's fitter parent, for judging whether mutation pays off
+    let mut parent_scores: Vec<Option<usize>> = vec![None; population.len()];
+    let mut one_fifth = (config.adaptive_mutation == AdaptiveMutation::OneFifth).then(|| OneFifthRule::new(config.mutation_rate));
+    // Individuals a crowding child displaced, restored if the child scores no higher
+    let mut incumbents: Vec<Option<(SUBLEQProgram, usize)>> = Vec::new();
+    let mut discoveries = Vec::new();
+    // Generation the search last started over from random programs after a solution
+    let mut restarted_at = progress.restarted_at;
+    let mut stop = StopReason::GenerationLimit;
+    let mut best: Option<(SUBLEQProgram, usize)> = progress.best;
+    let mut last_improvement = progress.last_improvement;
+    let (mut evaluated, mut last_stats) = (0, None);
+    let mut fitness_scores = Vec::new();
+    let mut objectives = Vec::new();
+    // Block mutations among the children bred into the generation about to be scored
+    let mut block_mutations = 0;
+    let mut first_full_copy = progress.first_full_copy;
+    for generation in start..config.generations {
+        // Saved before scoring, so a resumed run repeats this generation exactly
+        if config.checkpoint_every.is_some_and(|every| generation > start && generation.is_multiple_of(every)) {
+            let best = best.as_ref().map(|(program, score)| (program, *score));
+            let progress = Progress { best, last_improvement, restarted_at, first_full_copy };
+            checkpoint::save(&config.checkpoint_file, generation, &population, progress, &rng, config.format)?;
+            debug!("Checkpoint of generation {} written to {}", generation, config.checkpoint_file.display());
+        }
+        let executed: Vec<(usize, Evaluation)>;
+        let scoring_seed: u64 = stream_rng(run_seed, generation).gen();
+        (fitness_scores, executed) = in_pool(&pool, || match &mut cache {
+            Some(cache) => cache.evaluate_executed(&population, &vm, fitness.as_ref(), scoring_seed, config.verify_all),
+            None => {
+                // Individual i is scored with stream i, as in parallel breeding
+                let scored: Vec<Option<Evaluation>> = population
+                    .par_iter()
+                    .zip(&carried)
+                    .enumerate()
+                    .map(|(i, (p, known))| known.is_none().then(|| p.assess(&vm, fitness.as_ref(), &mut stream_rng(scoring_seed, i), config.verify_all)))
+                    .collect();
+                let scores = scored.iter().zip(&carried).map(|(evaluation, known)| evaluation.map_or(known.unwrap_or_default(), |e| e.score)).collect();
+                (scores, scored.into_iter().enumerate().filter_map(|(i, evaluation)| evaluation.map(|e| (i, e))).collect())
+            }
+        });
+        // Only new genomes can be the first to copy themselves or solve the objective;
+        // cached and carried ones were checked when they were first scored
+        let mut copied = executed.iter().any(|(_, evaluation)| evaluation.copied);
+        // Taken now, since crowding and immigrants may yet replace them
+        let mut solvers: Vec<(SUBLEQProgram, usize)> =
+            executed.iter().filter(|(_, evaluation)| evaluation.solved).map(|&(i, evaluation)| (population[i].clone(), evaluation.score)).collect();
+
+        // Every child is a trial, including crowding children about to be rejected
+        if let Some(rule) = &mut one_fifth {
+            let children = parent_scores.iter().zip(&fitness_scores).filter_map(|(parent, &score)| parent.map(|parent| score > parent));
+            let (successes, trials) = children.fold((0, 0), |(successes, trials), improved| (successes + usize::from(improved), trials + 1));
+            rule.record(successes, trials);
+        }
+        for (slot, incumbent) in incumbents.drain(..).enumerate() {
+            if let Some((program, score)) = incumbent.filter(|&(_, score)| fitness_scores[slot] <= score) {
+                population[slot] = program;
+                fitness_scores[slot] = score;
+            }
+        }
+
+        let mut immigrants = 0;
+        if config.immigrants > 0 && generation > 0 {
+            let arrivals = inject_immigrants(&mut population, &mut fitness_scores, &islands, &config, &vm, fitness.as_ref(), &mut rng);
+            immigrants = arrivals.len();
+            // Immigrants are new genomes too, checked as the bred children were
+            copied |= arrivals.iter().any(|(_, evaluation)| evaluation.copied);
+            solvers.extend(arrivals.iter().filter(|(_, evaluation)| evaluation.solved).map(|&(i, evaluation)| (population[i].clone(), evaluation.score)));
+            if let Some(lineage) = &mut lineage {
+                lineage.record(&mut population, generation);
+            }
+        }
+        if copied && first_full_copy.is_none() {
+            first_full_copy = Some(generation);
+            info!("Generation {}: first program to write a full copy of itself", generation);
+        }
+
+        if config.multiobjective {
+            objectives = in_pool(&pool, || population.par_iter().map(|p| p.objectives(&vm)).collect());
+        }
+
+        let best_at = best_index(&population, &fitness_scores);
+        let (best_program, best_fitness) = (&population[best_at], fitness_scores[best_at]);
+        if best.as_ref().is_none_or(|(_, fitness)| best_fitness > *fitness) {
+            best = Some((best_program.clone(), best_fitness));
+            last_improvement = generation;
+        }
+
+        // Raise the mutation rate for a while once progress stalls, then wait for a
+        // fresh stall before raising it again
+        if let Some(trigger) = config.stall_trigger {
+            match hypermutation_until {
+                Some(until) if generation >= until => {
+                    hypermutation_until = None;
+                    stall_reference = generation;
+                }
+                None if generation - last_improvement.max(stall_reference) >= trigger => {
+                    hypermutation_until = Some(generation + config.hypermutation_generations);
+                }
+                _ => {}
+            }
+        }
+        let hypermutation_scale = if hypermutation_until.is_some() { config.hypermutation_factor } else { 1.0 };
+        let mutation = MutationConfig {
+            rate_scale: hypermutation_scale * one_fifth.as_ref().map_or(1.0, OneFifthRule::scale),
+            ..config.mutation()
+        };
+
+        // Sampled from its own generator so that measuring never changes the run
+        let diversity = diversity(&population, config.diversity_samples, &vm, &mut ChaCha12Rng::seed_from_u64(generation as u64));
+
+        let stats = GenerationStats {
+            island_best: islands.iter().map(|range| *fitness_scores[range.clone()].iter().max().unwrap()).collect(),
+            immigrants,
+            block_mutations,
+            hypermutation: hypermutation_until.is_some(),
+            adaptive_rate: one_fifth.as_ref().map(OneFifthRule::rate),
+            verified: config.verify_all.then_some(solvers.len()),
+            first_full_copy,
+            ..GenerationStats::new(generation, &population, &fitness_scores, diversity, &vm)
+        };
+        if let Some(log) = &mut log {
+            log.write(&stats).map_err(|e| RunError::Log(log.path().to_path_buf(), e))?;
+        }
+        if let Some(log) = &mut jsonl {
+            log.write(&stats).map_err(|e| RunError::Log(log.path().to_path_buf(), e))?;
+        }
+        if let Some(hall_of_fame) = &mut hall_of_fame {
+            hall_of_fame.update(generation, &population, &fitness_scores);
+        }
+        evaluated += 1;
+        let flow = callback(stats.clone());
+        last_stats = Some(stats);
+        // Stopping here rather than before the next generation leaves the population
+        // and its scores in step for the report, and skips breeding a generation that
+        // would never be scored
+        let mut restart = false;
+        // The efficiency reward can rank a verified solver below the fittest program, so
+        // the shortest of them is taken instead
+        let (candidate, candidate_fitness) =
+            solvers.iter().min_by_key(|(p, _)| p.code().len()).map_or((best_program, best_fitness), |(p, fitness)| (p, *fitness));
+        if let Some(result) = candidate.solution(&vm, fitness.as_ref()) {
+            let known = discoveries.len();
+            let done = discover(&mut discoveries, candidate, result, generation, &config);
+            if let Some(hall_of_fame) = hall_of_fame.as_mut().filter(|_| discoveries.len() > known) {
+                hall_of_fame.add_solution(generation, candidate, candidate_fitness);
+            }
+            if done {
+                stop = StopReason::Replicated;
+                break;
+            }
+            restart = true;
+        }
+        if flow.is_break() {
+            stop = StopReason::Interrupted;
+            break;
+        }
+        if config.stall_generations.is_some_and(|stall| generation - last_improvement.max(restarted_at) >= stall) {
+            stop = StopReason::Stalled;
+            break;
+        }
+        if generation + 1 == config.generations {
+            break;
+        }
+        if config.max_time.is_some_and(|budget| started.elapsed() >= budget) {
+            stop = StopReason::TimeLimit;
+            break;
+        }
+
+        // Breeding from a solved population would only find the same solution again
+        if restart {
+            population = (0..population.len()).map(|_| random_program(&mut rng)).collect();
+            carried.fill(None);
+            parent_scores.fill(None);
+            block_mutations = 0;
+            (hypermutation_until, stall_reference, restarted_at) = (None, generation + 1, generation + 1);
+            debug!("Generation {}: starting over from random programs", generation);
+            if let Some(lineage) = &mut lineage {
+                lineage.record(&mut population, generation + 1);
+            }
+            continue;
+        }
+
+        if islands.len() > 1 && generation > 0 && generation.is_multiple_of(config.migrate_every) {
+            islands::migrate(&mut population, &mut fitness_scores, &islands, config.migrants);
+            debug!("Generation {}: {} migrants sent from each island", generation, config.migrants);
+        }
+        carried.clear();
+        parent_scores.clear();
+        block_mutations = 0;
+        // Each island is ranked on its own, as it breeds on its own
+        let ranking: Vec<usize> = if config.multiobjective {
+            islands.iter().flat_map(|range| pareto::selection_scores(&objectives[range.clone()])).collect()
+        } else {
+            Vec::new()
+        };
+        population = in_pool(&pool, || {
+            let mut next = Vec::with_capacity(population.len());
+            for range in &islands {
+                let scores = if config.multiobjective { &ranking[range.clone()] } else { &fitness_scores[range.clone()] };
+                let island = &population[range.clone()];
+                if config.mode == Mode::SteadyState {
+                    let (offspring, survivors, blocks) =
+                        replace_worst_with(island, scores, &config, &mutation, &mut parent_scores, &mut incumbents, &mut rng);
+                    next.extend(offspring);
+                    carried.extend(survivors);
+                    block_mutations += blocks;
+                } else {
+                    let (offspring, blocks) = next_generation_with(island, scores, &config, &mutation, &mut parent_scores, &mut rng);
+                    next.extend(offspring);
+                    block_mutations += blocks;
+                    carried.extend(std::iter::repeat_n(None, range.len()));
+                    incumbents.extend(std::iter::repeat_n(None, range.len()));
+                }
+            }
+            next
+        });
+        if config.local_search > 0 {
+            // Elites are copies of last generation's programs; everyone else without a
+            // carried score is a new child
+            let is_child = |i: usize| match config.mode {
+                Mode::SteadyState => carried[i].is_none(),
+                _ => islands.iter().all(|range| !(range.start..range.start + config.elitism.min(range.len() - 1)).contains(&i)),
+            };
+            let children: Vec<usize> = (0..population.len()).filter(|&i| is_child(i)).collect();
+            // Child i searches with stream i, as in parallel breeding
+            let search_seed: u64 = rng.gen();
+            let refined: Vec<SUBLEQProgram> = in_pool(&pool, || {
+                children
+                    .par_iter()
+                    .map(|&i| local_search(&population[i], config.local_search, &vm, fitness.as_ref(), &mut stream_rng(search_seed, i)))
+                    .collect()
+            });
+            // Not carried over, so the refined children are scored and checked for
+            // copies and solutions with the rest of the generation
+            for (&i, program) in children.iter().zip(refined) {
+                population[i] = program;
+            }
+        }
+        if let Some(lineage) = &mut lineage {
+            lineage.record(&mut population, generation + 1);
+        }
+    }
+
+    if let (Some(hall_of_fame), Some(path)) = (&hall_of_fame, &config.hall_of_fame) {
+        hall_of_fame.save(path, config.memory_size).map_err(|e| RunError::HallOfFame(path.clone(), e))?;
+    }
+    let summary = RunSummary::new(evaluated, started.elapsed(), best.as_ref(), last_stats.as_ref(), cache.as_ref());
+    let (best, best_fitness) = match best {
+        Some((program, fitness)) => (Some(program), fitness),
+        None => (None, 0),
+    };
+    if fitness_scores.is_empty() {
+        population.clear();
+    }
+    let pareto_front = if config.multiobjective { pareto::pareto_front(&population, &objectives) } else { Vec::new() };
+    let (replicator, replicator_result) = discoveries.first().map(|found| (found.program.clone(), found.result.clone())).unzip();
+    Ok(RunReport {
+        stop,
+        replicator,
+        replicator_result,
+        discoveries,
+        best,
+        best_fitness,
+        last_improvement,
+        population,
+        fitness_scores,
+        lineage,
+        pareto_front,
+        summary,
+    })
+}
+
+/// Climbs from one random program (or `seed_program`) by mutating a copy each
+/// generation and keeping it if it scores higher, as a baseline for the GA.
+///
+/// In anneal mode the mutant is also kept if it scores the same, or if it scores `loss`
+/// less with probability `exp(-loss / temperature)`; the temperature starts at
+/// `initial_temp` and is multiplied by `cooling` every generation.
+///
+/// The population and crossover settings are ignored. When the climb has not improved
+/// for `stall_generations`, it starts over from a new random program up to `restarts`
+/// times and then stops. `callback` sees the current program each generation, like the
+/// GA's, and `log_csv` and `log_jsonl` are written the same way.
+pub fn run_hill_climb(
+    config: GaConfig,
+    fitness: Box<dyn Fitness>,
+    mut callback: impl FnMut(GenerationStats) -> ControlFlow<()>,
+) -> Result<RunReport, RunError> {
+    config.validate().map_err(RunError::Config)?;
+    let started = Instant::now();
+    let vm = config.vm();
+    let mut mutation = config.mutation();
+    let mut one_fifth = (config.adaptive_mutation == AdaptiveMutation::OneFifth).then(|| OneFifthRule::new(config.mutation_rate));
+    let mut rng = match config.seed {
+        Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+        None => ChaCha12Rng::from_entropy(),
+    };
+    let mut log = match &config.log_csv {
+        Some(path) => Some(CsvLog::create(path).map_err(|e| RunError::Log(path.clone(), e))?),
+        None => None,
+    };
+    let mut jsonl = match &config.log_jsonl {
+        Some(path) => Some(JsonlLog::create(path).map_err(|e| RunError::Log(path.clone(), e))?),
+        None => None,
+    };
+    let random_program = |rng: &mut ChaCha12Rng| {
+        let length = config.init_length_bias.sample(config.min_len, config.max_len, rng);
+        SUBLEQProgram::new(length, config.memory_size, rng).with_mutation_rate(config.mutation_rate)
+    };
+
+    let mut current = match &config.seed_program {
+        Some(code) => SUBLEQProgram::from_code(code.clone()).with_mutation_rate(config.mutation_rate),
+        None => random_program(&mut rng),
+    };
+    let evaluation = current.assess(&vm, fitness.as_ref(), &mut rng, false);
+    let (mut current_fitness, copied) = (evaluation.score, evaluation.copied);
+    let mut first_full_copy = copied.then_some(0);
+    if copied {
+        info!("Generation 0: first program to write a full copy of itself");
+    }
+    let anneal = config.mode == Mode::Anneal;
+    let mut temperature = config.initial_temp;
+    let mut accepted_worse = 0;
+    let mut climb_improved = 0;
+    let mut restarts = 0;
+    let mut discoveries = Vec::new();
+    let mut stop = StopReason::GenerationLimit;
+    let mut best: Option<(SUBLEQProgram, usize)> = None;
+    let mut last_improvement = 0;
+    let (mut evaluated, mut last_stats) = (0, None);
+    // The climber as last scored, before any restart replaced it
+    let mut scored = None;
+    for generation in 0..config.generations {
+        if generation > 0 && config.max_time.is_some_and(|budget| started.elapsed() >= budget) {
+            stop = StopReason::TimeLimit;
+            break;
+        }
+        let mut block = false;
+        if generation > 0 {
+            let mut candidate = current.clone();
+            block = candidate.mutate(&mutation, &mut rng);
+            let evaluation = candidate.assess(&vm, fitness.as_ref(), &mut rng, false);
+            let (candidate_fitness, copied) = (evaluation.score, evaluation.copied);
+            if copied && first_full_copy.is_none() {
+                first_full_copy = Some(generation);
+                info!("Generation {}: first program to write a full copy of itself", generation);
+            }
+            let improved = candidate_fitness > current_fitness;
+            let accepted = match candidate_fitness.cmp(&current_fitness) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => anneal,
+                std::cmp::Ordering::Less => {
+                    let loss = (current_fitness - candidate_fitness) as f64;
+                    anneal && rng.gen::<f64>() < (-loss / temperature).exp()
+                }
+            };
+            if accepted {
+                accepted_worse += usize::from(candidate_fitness < current_fitness);
+                (current, current_fitness) = (candidate, candidate_fitness);
+            }
+            if improved {
+                climb_improved = generation;
+            }
+            temperature *= config.cooling;
+            // Each mutant is one trial of the current rate
+            if let Some(rule) = &mut one_fifth {
+                rule.record(usize::from(improved), 1);
+                mutation.rate_scale = rule.scale();
+            }
+        }
+        if best.as_ref().is_none_or(|(_, fitness)| current_fitness > *fitness) {
+            best = Some((current.clone(), current_fitness));
+            last_improvement = generation;
+        }
+
+        let stats = GenerationStats {
+            block_mutations: usize::from(block),
+            adaptive_rate: one_fifth.as_ref().map(OneFifthRule::rate),
+            temperature: anneal.then_some(temperature),
+            accepted_worse: anneal.then_some(accepted_worse),
+            first_full_copy,
+            ..GenerationStats::new(generation, std::slice::from_ref(&current), &[current_fitness], 0.0, &vm)
+        };
+        scored = Some((current.clone(), current_fitness));
+        if let Some(log) = &mut log {
+            log.write(&stats).map_err(|e| RunError::Log(log.path().to_path_buf(), e))?;
+        }
+        if let Some(log) = &mut jsonl {
+            log.write(&stats).map_err(|e| RunError::Log(log.path().to_path_buf(), e))?;
+        }
+        evaluated += 1;
+        let flow = callback(stats.clone());
+        last_stats = Some(stats);
+        if let Some(result) = current.solution(&vm, fitness.as_ref()) {
+            if discover(&mut discoveries, &current, result, generation, &config) {
+                stop = StopReason::Replicated;
+                break;
+            }
+            current = random_program(&mut rng);
+            current_fitness = current.evaluate(&vm, fitness.as_ref());
+            debug!("Generation {}: starting over from a random program", generation);
+            climb_improved = generation;
+            temperature = config.initial_temp;
+        }
+        if flow.is_break() {
+            stop = StopReason::Interrupted;
+            break;
+        }
+        if config.stall_generations.is_some_and(|stall| generation - climb_improved >= stall) {
+            if restarts == config.restarts {
+                stop = StopReason::Stalled;
+                break;
+            }
+            restarts += 1;
+            current = random_program(&mut rng);
+            current_fitness = current.evaluate(&vm, fitness.as_ref());
+            debug!("Generation {}: restart {} of {} from a random program", generation, restarts, config.restarts);
+            climb_improved = generation;
+            temperature = config.initial_temp;
+        }
+    }
+
+    let summary = RunSummary::new(evaluated, started.elapsed(), best.as_ref(), last_stats.as_ref(), None);
+    let (best, best_fitness) = match best {
+        Some((program, fitness)) => (Some(program), fitness),
+        None => (None, 0),
+    };
+    let (population, fitness_scores) = scored.into_iter().unzip();
+    let (replicator, replicator_result) = discoveries.first().map(|found| (found.program.clone(), found.result.clone())).unzip();
+    Ok(RunReport {
+        stop,
+        replicator,
+        replicator_result,
+        discoveries,
+        best,
+        best_fitness,
+        last_improvement,
+        population,
+        fitness_scores,
+        lineage: None,
+        pareto_front: Vec::new(),
+        summary,
+    })
+}
+
+/// Records `program`, which solved the objective in `generation`, unless it is within
+/// `find_distance` edits of an earlier discovery; true once `find` have been made.
+fn discover(discoveries: &mut Vec<Discovery>, program: &SUBLEQProgram, result: ExecutionResult, generation: usize, config: &GaConfig) -> bool {
+    if discoveries.iter().all(|found| program_distance(&found.program, program) > config.find_distance) {
+        if config.find > 1 {
+            info!("Generation {}: solution {} of {} found ({} words)", generation, discoveries.len() + 1, config.find, program.code().len());
+        }
+        discoveries.push(Discovery { generation, program: program.clone(), result });
+    }
+    discoveries.len() == config.find
+}
+
+/// Replaces the weakest offspring on each island with random programs, scored as
+/// [`SUBLEQProgram::assess`] scores bred children, and returns their slots and what
+/// scoring them found.
+///
+/// Up to `config.immigrants` per island are replaced, always sparing the elites at the
+/// front of the island and at least one other individual. The immigrant in slot `i`
+/// is scored with stream `i` of a seed drawn from `rng`.
+fn inject_immigrants(
+    population: &mut [SUBLEQProgram],
+    fitness_scores: &mut [usize],
+    islands: &[std::ops::Range<usize>],
+    config: &GaConfig,
+    vm: &VmConfig,
+    fitness: &dyn Fitness,
+    rng: &mut impl Rng,
+) -> Vec<(usize, Evaluation)> {
+    let seed: u64 = rng.gen();
+    let mut injected = Vec::new();
+    for range in islands {
+        let elites = config.elitism.min(range.len() - 1);
+        let mut offspring: Vec<usize> = (range.start + elites..range.end).collect();
+        offspring.sort_by_key(|&i| fitness_scores[i]);
+        for &slot in offspring.iter().take(config.immigrants.min(offspring.len() - 1)) {
+            let length = rng.gen_range(config.min_len..=config.max_len);
+            population[slot] = SUBLEQProgram::new(length, config.memory_size, rng).with_mutation_rate(config.mutation_rate);
+            let evaluation = population[slot].assess(vm, fitness, &mut stream_rng(seed, population.len() + slot), config.verify_all);
+            fitness_scores[slot] = evaluation.score;
+            injected.push((slot, evaluation));
+        }
+    }
+    injected
+}
+
+/// Refines `program` by up to `moves` single-gene hill-climbing moves, each replacing
+/// one random gene and kept only if it raises the fitness.
+fn local_search(program: &SUBLEQProgram, moves: usize, vm: &VmConfig, fitness: &dyn Fitness, rng: &mut impl Rng) -> SUBLEQProgram {
+    let mut best = program.clone();
+    if best.code.is_empty() {
+        return best;
+    }
+    let mut best_score = best.evaluate(vm, fitness);
+    for _ in 0..moves {
+        let mut candidate = best.clone();
+        let gene = rng.gen_range(0..candidate.code.len());
+        candidate.code[gene] = rng.gen_range(-vm.memory_size..vm.memory_size);
+        let score = candidate.evaluate(vm, fitness);
+        if score > best_score {
+            (best, best_score) = (candidate, score);
+        }
+    }
+    best
+}
+
+/// Runs `op` in `pool`, so that its parallel iterators use that pool's threads, or in
+/// the global pool if there is none.
+fn in_pool<R: Send>(pool: &Option<ThreadPool>, op: impl FnOnce() -> R + Send) -> R {
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Runs a soup seeded according to `config` for `generations` rounds, or until every
+/// organism has died, and returns its final state.
+pub fn run_soup(config: &GaConfig) -> Soup {
+    let mut rng = match config.seed {
+        Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+        None => ChaCha12Rng::from_entropy(),
+    };
+    let mut soup = Soup::new(config, &mut rng);
+    while soup.round() < config.generations && !soup.is_empty() {
+        soup.step_round();
+        let stats = soup.stats();
+        info!(
+            "Round {}: {} organisms, {} births, {} deaths, {} free cells",
+            soup.round() - 1,
+            soup.len(),
+            stats.births,
+            stats.deaths,
+            soup.free_cells()
+        );
+    }
+    soup
+}
+
+/// Evolves a population of hosts, scored by `fitness`, against an equal population of
+/// parasites scored on how much of themselves the hosts copy (see [`encounter`]), for
+/// `generations` generations. Each generation every host meets one parasite, paired at
+/// random, and each population then breeds on its own as by [`next_generation`].
+///
+/// `seed_program` seeds the hosts only. The run never stops early, and islands,
+/// steady-state replacement, logs and checkpoints are not used.
+pub fn run_coevolution(config: &GaConfig, fitness: &dyn Fitness) -> Result<Coevolution, RunError> {
+    config.validate().map_err(RunError::Config)?;
+    let vm = config.vm();
+    let mut rng = match config.seed {
+        Some(seed) => ChaCha12Rng::seed_from_u64(seed),
+        None => ChaCha12Rng::from_entropy(),
+    };
+    let random_program = |rng: &mut ChaCha12Rng| {
+        let length = config.init_length_bias.sample(config.min_len, config.max_len, rng);
+        SUBLEQProgram::new(length, config.memory_size, rng).with_mutation_rate(config.mutation_rate)
+    };
+    let mut hosts: Vec<SUBLEQProgram> = (0..config.population).map(|_| random_program(&mut rng)).collect();
+    if let Some(code) = &config.seed_program {
+        for slot in config.seed_slots() {
+            hosts[slot] = SUBLEQProgram::from_code(code.clone()).with_mutation_rate(config.mutation_rate);
+        }
+    }
+    let mut parasites: Vec<SUBLEQProgram> = (0..config.population).map(|_| random_program(&mut rng)).collect();
+    #[cfg(feature = "parallel")]
+    let pool = match config.threads {
+        0 => None,
+        threads => Some(ThreadPoolBuilder::new().num_threads(threads).build().map_err(RunError::ThreadPool)?),
+    };
+    #[cfg(not(feature = "parallel"))]
+    let pool: Option<ThreadPool> = None;
+    let mut best_host: Option<(SUBLEQProgram, usize)> = None;
+    let mut best_parasite: Option<(SUBLEQProgram, usize)> = None;
+    let (mut host_scores, mut parasite_scores) = (Vec::new(), Vec::new());
+    let mut generations = 0;
+    for generation in 0..config.generations {
+        // Host i meets parasite partners[i]
+        let mut partners: Vec<usize> = (0..parasites.len()).collect();
+        partners.shuffle(&mut rng);
+        let encounters: Vec<Encounter> =
+            in_pool(&pool, || hosts.par_iter().zip(&partners).map(|(host, &partner)| encounter(host, &parasites[partner], &vm, fitness)).collect());
+        host_scores = encounters.iter().map(|e| e.host).collect();
+        parasite_scores = vec![0; parasites.len()];
+        for (e, &partner) in encounters.iter().zip(&partners) {
+            parasite_scores[partner] = e.parasite;
+        }
+        generations += 1;
+
+        for (best, population, scores) in [(&mut best_host, &hosts, &host_scores), (&mut best_parasite, &parasites, &parasite_scores)] {
+            let at = best_index(population, scores);
+            if best.as_ref().is_none_or(|(_, score)| scores[at] > *score) {
+                *best = Some((population[at].clone(), scores[at]));
+            }
+        }
+        let mean = |scores: &[usize]| scores.iter().sum::<usize>() as f64 / scores.len() as f64;
+        info!(
+            "Generation {}: best host {} (mean {:.2}), best parasite {} (mean {:.2})",
+            generation,
+            host_scores.iter().max().unwrap(),
+            mean(&host_scores),
+            parasite_scores.iter().max().unwrap(),
+            mean(&parasite_scores)
+        );
+        if generation + 1 == config.generations {
+            break;
+        }
+        hosts = in_pool(&pool, || next_generation(&hosts, &host_scores, config, &mut rng));
+        parasites = in_pool(&pool, || next_generation(&parasites, &parasite_scores, config, &mut rng));
+    }
+    Ok(Coevolution { generations, hosts, host_scores, parasites, parasite_scores, best_host, best_parasite })
+}
+
+/// Breeds the generation that follows `population`, given its fitness scores: the
+/// elites carried over, then offspring of selected parents until it is as large as
+/// `population`.
+pub fn next_generation(
+    population: &[SUBLEQProgram],
+    fitness_scores: &[usize],
+    config: &GaConfig,
+    rng: &mut impl Rng,
+) -> Vec<SUBLEQProgram> {
+    next_generation_with(population, fitness_scores, config, &config.mutation(), &mut Vec::new(), rng).0
+}
+
+/// [`next_generation`] with explicit mutation parameters; appends the score of each
+/// new individual's fitter parent to `parent_scores`, or `None` for an elite, and also
+/// returns the number of children that underwent a block mutation.
+fn next_generation_with(
+    population: &[SUBLEQProgram],
+    fitness_scores: &[usize],
+    config: &GaConfig,
+    mutation: &MutationConfig,
+    parent_scores: &mut Vec<Option<usize>>,
+    rng: &mut impl Rng,
+) -> (Vec<SUBLEQProgram>, usize) {
+    let selector = Selector::new(config, population, fitness_scores);
+    let mut new_population = Vec::with_capacity(population.len());
+
+    // Carry the top individuals over unchanged; always leave room for at least one child
+    let elites = config.elitism.min(population.len() - 1);
+    if elites > 0 {
+        let mut ranked: Vec<usize> = (0..population.len()).collect();
+        ranked.sort_by(|&i, &j| fitness_scores[j].cmp(&fitness_scores[i]));
+        new_population.extend(ranked[..elites].iter().map(|&i| population[i].clone()));
+    }
+
+    parent_scores.extend(std::iter::repeat_n(None, new_population.len()));
+
+    let children = population.len() - new_population.len();
+    let (children, blocks) = breed_all(population, &selector, config, mutation, children, rng);
+    for (child, parent_score) in children {
+        new_population.push(child);
+        parent_scores.push(Some(parent_score));
+    }
+    (new_population, blocks)
+}
+
+/// One steady-state step: replaces the worst `gap` fraction of `population` (at least
+/// one individual) with offspring of selected parents, in place of the evicted ones.
+/// Under [`Replacement::Crowding`] each child is placed over its most similar
+/// individual instead, which it displaces unconditionally here.
+///
+/// Returns the new population with the scores of the survivors, which are `None`
+/// where a child needs evaluating.
+pub fn replace_worst(
+    population: &[SUBLEQProgram],
+    fitness_scores: &[usize],
+    config: &GaConfig,
+    rng: &mut impl Rng,
+) -> (Vec<SUBLEQProgram>, Vec<Option<usize>>) {
+    let (population, scores, _) = replace_worst_with(population, fitness_scores, config, &config.mutation(), &mut Vec::new(), &mut Vec::new(), rng);
+    (population, scores)
+}
+
+/// [`replace_worst`] with explicit mutation parameters, also reporting parents' scores
+/// and block mutations as [`next_generation_with`] does, with `None` for survivors. Appends to
+/// `incumbents` each scored individual a crowding child displaced, or `None`.
+fn replace_worst_with(
+    population: &[SUBLEQProgram],
+    fitness_scores: &[usize],
+    config: &GaConfig,
+    mutation: &MutationConfig,
+    parent_scores: &mut Vec<Option<usize>>,
+    incumbents: &mut Vec<Option<(SUBLEQProgram, usize)>>,
+    rng: &mut impl Rng,
+) -> (Vec<SUBLEQProgram>, Vec<Option<usize>>, usize) {
+    let selector = Selector::new(config, population, fitness_scores);
+    let replaced = ((config.gap * population.len() as f64).round() as usize).clamp(1, population.len());
+    let (children, blocks) = breed_all(population, &selector, config, mutation, replaced, rng);
+
+    let mut new_population = population.to_vec();
+    let mut scores: Vec<Option<usize>> = fitness_scores.iter().copied().map(Some).collect();
+    let first = parent_scores.len();
+    parent_scores.extend(std::iter::repeat_n(None, population.len()));
+    incumbents.extend(std::iter::repeat_n(None, population.len()));
+    let mut place = |slot: usize, (child, parent_score): (SUBLEQProgram, usize)| {
+        new_population[slot] = child;
+        scores[slot] = None;
+        parent_scores[first + slot] = Some(parent_score);
+    };
+    match config.replacement {
+        Replacement::Worst => {
+            // Stable, so among equally bad individuals the earliest are evicted first
+            let mut ranked: Vec<usize> = (0..population.len()).collect();
+            ranked.sort_by_key(|&i| fitness_scores[i]);
+            for (&slot, child) in ranked.iter().zip(children) {
+                place(slot, child);
+            }
+        }
+        Replacement::Crowding => {
+            // Each individual faces at most one challenger per step
+            let mut taken = vec![false; population.len()];
+            for child in children {
+                let slot = most_similar(population, &taken, child.0.code()).expect("a child per individual at most");
+                taken[slot] = true;
+                incumbents[first + slot] = Some((population[slot].clone(), fitness_scores[slot]));
+                place(slot, child);
+            }
+        }
+    }
+    (new_population, scores, blocks)
+}
+
+/// Generator for the `index`th of a batch of tasks seeded with `seed`, which draws the
+/// same numbers whichever thread runs the task.
+pub(crate) fn stream_rng(seed: u64, index: usize) -> ChaCha12Rng {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    rng.set_stream(index as u64);
+    rng
+}
+
+fn breed_all(
+    population: &[SUBLEQProgram],
+    selector: &Selector,
+    config: &GaConfig,
+    mutation: &MutationConfig,
+    children: usize,
+    rng: &mut impl Rng,
+) -> (Vec<(SUBLEQProgram, usize)>, usize) {
+    let bred: Vec<(SUBLEQProgram, usize, bool)> = if config.parallel_breeding {
+        // Child i draws from stream i of a generator seeded by the main one, so the
+        // offspring do not depend on how rayon schedules them
+        let breeding_seed: u64 = rng.gen();
+        (0..children)
+            .into_par_iter()
+            .map(|i| breed(population, selector, config, mutation, &mut stream_rng(breeding_seed, i)))
+            .collect()
+    } else {
+        (0..children).map(|_| breed(population, selector, config, mutation, rng)).collect()
+    };
+    let blocks = bred.iter().filter(|&&(_, _, block)| block).count();
+    (bred.into_iter().map(|(child, parent_score, _)| (child, parent_score)).collect(), blocks)
+}
+
+fn breed(
+    population: &[SUBLEQProgram],
+    selector: &Selector,
+    config: &GaConfig,
+    mutation: &MutationConfig,
+    rng: &mut impl Rng,
+) -> (SUBLEQProgram, usize, bool) {
+    let (mut child, parent_score) = if config.no_crossover {
+        let i = selector.select(rng);
+        (crossover::clone_parent(&population[i]), selector.fitness(i))
+    } else {
+        let (i, j) = (selector.select(rng), selector.select(rng));
+        (config.crossover.apply(&population[i], &population[j], config.memory_size, rng), selector.fitness(i).max(selector.fitness(j)))
+    };
+    let block = child.mutate(mutation, rng);
+    (child, parent_score, block)
+}
+
+/// Why [`run`] stopped before finishing.
+#[derive(Debug)]
+pub enum RunError {
+    Log(PathBuf, io::Error),
+    Checkpoint(PathBuf, io::Error),
+    InvalidCheckpoint(PathBuf, serde_json::Error),
+    #[cfg(feature = "bincode")]
+    InvalidBinaryCheckpoint(PathBuf, bincode::Error),
+    CheckpointVersion { path: PathBuf, version: u64 },
+    CheckpointExceedsMemory { path: PathBuf, len: usize, memory_size: i32 },
+    /// The checkpoint holds fewer than two programs per island.
+    CheckpointTooSmall { path: PathBuf, len: usize, islands: usize },
+    HallOfFame(PathBuf, io::Error),
+    Config(ConfigError),
+    #[cfg(feature = "parallel")]
+    ThreadPool(ThreadPoolBuildError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Log(path, e) => write!(f, "cannot write log {}: {}", path.display(), e),
+            RunError::Checkpoint(path, e) => write!(f, "cannot access checkpoint {}: {}", path.display(), e),
+            RunError::InvalidCheckpoint(path, e) => write!(f, "invalid checkpoint {}: {}", path.display(), e),
+            #[cfg(feature = "bincode")]
+            RunError::InvalidBinaryCheckpoint(path, e) => write!(f, "invalid checkpoint {}: {}", path.display(), e),
+            RunError::CheckpointVersion { path, version } => write!(
+                f,
+                "checkpoint {} has format version {}, but this build reads version {}",
+                path.display(),
+                version,
+                CHECKPOINT_VERSION
+            ),
+            RunError::CheckpointExceedsMemory { path, len, memory_size } => write!(
+                f,
+                "checkpoint {} holds a program of {} words, which does not fit in memory_size ({})",
+                path.display(),
+                len,
+                memory_size
+            ),
+            RunError::CheckpointTooSmall { path, len, islands } => write!(
+                f,
+                "checkpoint {} holds {} programs, fewer than two for each of {} islands",
+                path.display(),
+                len,
+                islands
+            ),
+            RunError::HallOfFame(path, e) => write!(f, "cannot write hall of fame {}: {}", path.display(), e),
+            RunError::Config(e) => write!(f, "invalid configuration: {}", e),
+            #[cfg(feature = "parallel")]
+            RunError::ThreadPool(e) => write!(f, "cannot start thread pool: {}", e),
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::Log(_, e) | RunError::Checkpoint(_, e) | RunError::HallOfFame(_, e) => Some(e),
+            RunError::InvalidCheckpoint(_, e) => Some(e),
+            #[cfg(feature = "bincode")]
+            RunError::InvalidBinaryCheckpoint(_, e) => Some(e),
+            RunError::Config(e) => Some(e),
+            #[cfg(feature = "parallel")]
+            RunError::ThreadPool(e) => Some(e),
+            RunError::CheckpointVersion { .. } | RunError::CheckpointExceedsMemory { .. } | RunError::CheckpointTooSmall { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vm() -> VmConfig {
+        GaConfig { memory_size: 8, max_steps: 50, ..GaConfig::default() }.vm()
+    }
+
+    /// Subtracts zero data cells and jumps 0 -> 3 -> 6, the end of an eight-cell memory.
+    fn runs_to_the_end() -> SUBLEQProgram {
+        SUBLEQProgram::from_code(vec![6, 7, 3, 6, 7, 6])
+    }
+
+    /// Subtracts zero data cells and jumps back to itself forever.
+    fn spins() -> SUBLEQProgram {
+        SUBLEQProgram::from_code(vec![6, 7, 0])
+    }
+
+    #[test]
+    fn falls_off_the_end() {
+        let result = runs_to_the_end().execute(&vm());
+        assert!(result.halted);
+        assert_eq!(result.steps, 2);
+    }
+
+    #[test]
+    fn restart_at_end_runs_to_the_step_cap() {
+        let result = runs_to_the_end().execute(&VmConfig { restart_at_end: true, ..vm() });
+        assert!(!result.halted);
+        assert_eq!(result.steps, 50);
+    }
+
+    #[test]
+    fn halts_on_a_negative_branch() {
+        let program = SUBLEQProgram::from_code(vec![6, 7, -1]);
+        let restarting = VmConfig { restart_at_end: true, ..vm() };
+        let result = program.execute(&VmConfig { halt_on_negative: true, ..restarting.clone() });
+        assert!(result.halted);
+        assert_eq!(result.steps, 1);
+        assert!(!program.execute(&restarting).halted, "without the flag -1 wraps to the last cell");
+    }
+
+    #[test]
+    fn halts_when_input_runs_out() {
+        let io = VmConfig { io_port: Some(7), input: vec![5], restart_at_end: true, ..vm() };
+        let result = SUBLEQProgram::from_code(vec![6, 7, 3, 6, 7, 3]).execute(&io);
+        assert!(result.halted);
+        assert_eq!(result.steps, 2, "the first read succeeds and the second halts");
+        assert_eq!(result.memory[6], 5);
+    }
+
+    #[test]
+    fn detect_cycles_stops_a_loop() {
+        assert_eq!(spins().execute(&vm()).steps, 50);
+        let result = spins().execute(&VmConfig { detect_cycles: true, ..vm() });
+        assert!(result.cycled);
+        assert!(result.steps < 50);
+    }
+
+    #[test]
+    fn replicates_the_same_at_any_load_offset() {
+        // Addresses are absolute, so only a genome that never addresses its own cells
+        // runs the same wherever it lies; zeros clear cell 0 and jump there forever
+        let program = SUBLEQProgram::from_code(vec![0; 6]);
+        let vm = GaConfig { memory_size: 32, max_steps: 50, ..GaConfig::default() }.vm();
+        let fitness = ReplicationFitness::default();
+        let at = |load_offset| {
+            let vm = VmConfig { load_offset, ..vm.clone() };
+            let result = program.execute(&vm);
+            let score = program.with_execution(&vm, |result| fitness.score(&result, &program));
+            (result.memory, result.steps, score, program.verify_replication(&vm))
+        };
+        let origin = at(0);
+        assert!(origin.3, "blank memory holds copies of the genome");
+        assert_eq!(at(5), origin);
+    }
+
+    #[test]
+    fn rejects_populations_too_small_to_breed() {
+        for population in [0, 1] {
+            let config = GaConfig { population, generations: 3, ..GaConfig::default() };
+            match run(config, Box::new(ReplicationFitness::default())) {
+                Err(RunError::Config(ConfigError::PopulationTooSmall { population: found, min: 2 })) => assert_eq!(found, population),
+                Err(e) => panic!("population {} failed with {}", population, e),
+                Ok(_) => panic!("population {} ran", population),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_programs_longer_than_memory() {
+        let config = GaConfig { memory_size: 16, max_len: 17, ..GaConfig::default() };
+        match run(config, Box::new(ReplicationFitness::default())) {
+            Err(RunError::Config(ConfigError::ProgramExceedsMemory { max_len: 17, memory_size: 16 })) => {}
+            Err(e) => panic!("failed with {}", e),
+            Ok(_) => panic!("a 17-word program ran in 16 cells"),
+        }
+    }
+
+    #[test]
+    fn resumes_a_stalling_run_on_the_same_trajectory() {
+        let path = std::env::temp_dir().join(format!("subleq-resume-{}.json", std::process::id()));
+        let config = GaConfig {
+            seed: Some(7),
+            population: 100,
+            tournament_size: 1,
+            stall_generations: Some(8),
+            checkpoint_every: Some(12),
+            checkpoint_file: path.clone(),
+            ..GaConfig::default()
+        };
+        let straight = run(config.clone(), Box::new(ReplicationFitness::default())).unwrap();
+        let resumed = run(GaConfig { resume: Some(path.clone()), ..config }, Box::new(ReplicationFitness::default())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(straight.stop, StopReason::Stalled);
+        assert!(straight.last_improvement < 12, "the stall spans the checkpoint");
+        assert_eq!(resumed.stop, straight.stop);
+        assert_eq!(resumed.last_improvement, straight.last_improvement);
+        assert_eq!(resumed.best_fitness, straight.best_fitness);
+        assert_eq!(resumed.best.map(|p| p.code().to_vec()), straight.best.map(|p| p.code().to_vec()));
+        assert_eq!(resumed.fitness_scores, straight.fitness_scores);
+    }
+
+    /// Counts every program a solution, scoring it by length.
+    struct Anything;
+
+    impl Fitness for Anything {
+        fn score(&self, _result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> usize {
+            program.code().len()
+        }
+
+        fn is_solution(&self, _result: &ExecutionResult<&mut [i32]>, _program: &SUBLEQProgram) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn records_every_discovery_in_the_hall_of_fame() {
+        let path = std::env::temp_dir().join(format!("subleq-hall-{}.json", std::process::id()));
+        let config = GaConfig { seed: Some(3), population: 20, find: 3, hall_of_fame: Some(path.clone()), hall_of_fame_size: 1, ..GaConfig::default() };
+        let report = run(config, Box::new(Anything)).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(report.discoveries.len(), 3);
+        assert_eq!(saved["entries"].as_array().unwrap().len(), 1);
+        let solutions = saved["solutions"].as_array().unwrap();
+        for (solution, found) in solutions.iter().zip(&report.discoveries) {
+            assert_eq!(solution["generation"], found.generation);
+            assert_eq!(solution["code"], serde_json::json!(found.program.code()));
+        }
+        assert_eq!(solutions.len(), 3);
+    }
+
+    /// Scores programs by length and never counts one a solution.
+    struct Length;
+
+    impl Fitness for Length {
+        fn score(&self, _result: &ExecutionResult<&mut [i32]>, program: &SUBLEQProgram) -> usize {
+            program.code().len()
+        }
+    }
+
+    #[test]
+    fn resuming_keeps_the_first_full_copy() {
+        let path = std::env::temp_dir().join(format!("subleq-first-copy-{}.json", std::process::id()));
+        // Blank memory already holds copies of an all-zero genome
+        let config = GaConfig {
+            seed: Some(5),
+            population: 20,
+            generations: 10,
+            seed_program: Some(vec![0; 6]),
+            checkpoint_every: Some(8),
+            checkpoint_file: path.clone(),
+            ..GaConfig::default()
+        };
+        let straight = run(config.clone(), Box::new(Length)).unwrap();
+        let resumed = run(GaConfig { resume: Some(path.clone()), ..config }, Box::new(Length)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(straight.summary.first_full_copy, Some(0));
+        assert_eq!(resumed.summary.first_full_copy, Some(0));
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::SUBLEQProgram;
+
+/// Where an individual came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineageRecord {
+    /// Ids of the two parents, or `None` for a member of the initial population.
+    pub parents: Option<(u64, u64)>,
+    /// Generation in which the individual was born.
+    pub generation: usize,
+}
+
+/// Parentage of every individual born during a run, by id.
+#[derive(Clone, Debug, Default)]
+pub struct Lineage {
+    records: HashMap<u64, LineageRecord>,
+    next_id: u64,
+}
+
+impl Lineage {
+    /// Starts tracking `population`, giving ids to its untracked members.
+    ///
+    /// Members that already carry ids, as in a resumed checkpoint, keep them, but their
+    /// ancestry from before this point is unknown.
+    pub fn new(population: &mut [SUBLEQProgram], generation: usize) -> Self {
+        let next_id = population.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+        let mut lineage = Lineage { records: HashMap::new(), next_id };
+        lineage.record(population, generation);
+        lineage
+    }
+
+    /// Gives ids to the newborns of `population`, which are recognized by having none yet,
+    /// and records them as born in `generation`.
+    pub fn record(&mut self, population: &mut [SUBLEQProgram], generation: usize) {
+        for program in population {
+            if program.id == 0 {
+                program.id = self.next_id;
+                self.next_id += 1;
+            }
+            self.records.entry(program.id).or_insert(LineageRecord { parents: program.parents, generation });
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<LineageRecord> {
+        self.records.get(&id).copied()
+    }
+
+    /// Every known ancestor of `id`, and `id` itself, each listed once, nearest first.
+    pub fn ancestry(&self, id: u64) -> Vec<(u64, LineageRecord)> {
+        let mut ancestry = Vec::new();
+        let mut seen = HashSet::from([id]);
+        let mut queue = VecDeque::from([id]);
+        while let Some(id) = queue.pop_front() {
+            let Some(record) = self.get(id) else { continue };
+            ancestry.push((id, record));
+            if let Some((a, b)) = record.parents {
+                queue.extend([a, b].into_iter().filter(|&parent| seen.insert(parent)));
+            }
+        }
+        ancestry.sort_by(|x, y| y.1.generation.cmp(&x.1.generation).then(x.0.cmp(&y.0)));
+        ancestry
+    }
+
+    /// The chain of first parents leading from the initial population to `id`, oldest first.
+    pub fn line_of_descent(&self, id: u64) -> Vec<(u64, LineageRecord)> {
+        let mut line = Vec::new();
+        let mut id = id;
+        while let Some(record) = self.get(id) {
+            line.push((id, record));
+            match record.parents {
+                Some((first, _)) => id = first,
+                None => break,
+            }
+        }
+        line.reverse();
+        line
+    }
+
+    /// Writes the ancestry of `id` as CSV rows of `id,parent1,parent2,generation`.
+    pub fn save_ancestry(&self, id: u64, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "id,parent1,parent2,generation")?;
+        for (id, record) in self.ancestry(id) {
+            match record.parents {
+                Some((a, b)) => writeln!(writer, "{},{},{},{}", id, a, b, record.generation)?,
+                None => writeln!(writer, "{},,,{}", id, record.generation)?,
+            }
+        }
+        writer.flush()
+    }
+}
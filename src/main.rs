@@ -1,154 +1,1331 @@
-use rand::Rng;
-use rayon::prelude::*;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-const POPULATION_SIZE: usize = 10000;
-const GENERATIONS: usize = 10000;
-const MUTATION_RATE: f64 = 0.05;
-const MIN_PROGRAM_LENGTH: usize = 6;
-const MAX_PROGRAM_LENGTH: usize = 64;
-const MEMORY_SIZE: i32 = 256;
-const MAX_EXECUTION_STEPS: usize = 1000;
+use clap::{CommandFactory, Parser, Subcommand};
+use log::{debug, info, warn, Level, LevelFilter};
+use subleq_self_replicator::{
+    assemble, cluster, disassemble, minimize, gene_stats, parse_duration, run_coevolution, run_hill_climb, run_with_callback, run_soup, save_gene_stats, AdaptiveMutation, Cluster, Crossover, ExecutionResult, FileFormat, Fitness, FitnessMode, GaConfig, GenerationStats, HammingTargetFitness, InstructionSet, LengthBias, MemoryInit, RunSummary, Lineage, LineageRecord, Mode, MutationOperator, Objectives, ReplicationFitness,
+    ReplicationReport, Replacement, SUBLEQProgram, Selection, StepRecord, StopReason, VmConfig,
+};
 
-#[derive(Clone)]
-struct SUBLEQProgram {
-    code: Vec<i32>,
+#[cfg(feature = "plot")]
+mod plot;
+#[cfg(feature = "tui")]
+mod tui;
+
+/// Exit status of a run stopped by Ctrl-C or from the dashboard, as shells report for SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Number of largest clusters listed by `--cluster`; `--cluster-json` has them all.
+const CLUSTERS_SHOWN: usize = 10;
+
+/// Cells per row of the `--dump-memory` grid.
+const DUMP_COLUMNS: usize = 16;
+
+/// Buckets of the generation-0 length histogram and of `--report-every` charts.
+const LENGTH_BUCKETS: usize = 10;
+
+/// Characters in the longest bar of a `--report-every` chart.
+const CHART_WIDTH: usize = 40;
+
+/// Set by the Ctrl-C handler; the run stops at the end of the current generation.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Parser)]
+#[command(about = "Evolve self-replicating SUBLEQ programs")]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Kind of simulation to run
+    #[arg(long, value_enum)]
+    mode: Option<Mode>,
+
+    /// TOML file with experiment parameters; flags given here override it
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print an instruction-by-instruction trace of the program saved in this file (- for stdin) instead of evolving; read as assembly with --format asm
+    #[arg(long, value_name = "PROGRAM.json")]
+    debug: Option<PathBuf>,
+
+    /// Execute the program saved in this file (- for stdin) and check whether it replicates, instead of evolving; read as assembly with --format asm
+    #[arg(long, value_name = "PROGRAM.json")]
+    run: Option<PathBuf>,
+
+    /// Validate the configuration, print it with the search-space size and estimated memory use, and exit without evolving
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Save the self-replicator to this JSON file if one is found, or the best program if the run is interrupted, which without this flag is saved to best.json (best.bin or best.asm in those formats)
+    #[arg(long, value_name = "FILE.json")]
+    output: Option<PathBuf>,
+
+    /// Show a live dashboard instead of per-generation lines; q stops the run and saves the best program
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
+
+    /// Number of programs in each generation
+    #[arg(long)]
+    population: Option<usize>,
+
+    /// Maximum number of generations to evolve
+    #[arg(long)]
+    generations: Option<usize>,
+
+    /// Stop early once the best fitness has not improved for this many generations
+    #[arg(long, value_name = "N")]
+    stall_generations: Option<usize>,
+
+    /// Stop starting new generations once this much time has passed, e.g. 30s, 5m or 1.5h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    max_time: Option<Duration>,
+
+    /// Multiply mutation rates by --hypermutation-factor once the best fitness has not improved for this many generations
+    #[arg(long, value_name = "N")]
+    stall_trigger: Option<usize>,
+
+    /// Factor mutation rates are raised by during hypermutation
+    #[arg(long, requires = "stall_trigger")]
+    hypermutation_factor: Option<f64>,
+
+    /// Number of generations hypermutation lasts
+    #[arg(long, value_name = "N", requires = "stall_trigger")]
+    hypermutation_generations: Option<usize>,
+
+    /// Collect this many distinct solutions, starting over from random programs after each
+    #[arg(long, value_name = "N")]
+    find: Option<usize>,
+
+    /// Edit distance a solution must exceed from every earlier one to count as distinct
+    #[arg(long, value_name = "EDITS", requires = "find")]
+    find_distance: Option<usize>,
+
+    /// Check every new program each generation for a solution, not just the fittest, and keep the shortest found
+    #[arg(long)]
+    verify_all: bool,
+
+    /// Restart the hill climber from a new random program this many times when it stalls
+    #[arg(long, requires = "stall_generations")]
+    restarts: Option<usize>,
+
+    /// Starting temperature of --mode anneal, on the scale of fitness differences
+    #[arg(long)]
+    initial_temp: Option<f64>,
+
+    /// Factor the annealing temperature is multiplied by every iteration
+    #[arg(long)]
+    cooling: Option<f64>,
+
+    /// Per-gene probability of being replaced during mutation
+    #[arg(long)]
+    mutation_rate: Option<f64>,
+
+    /// Learning rate for per-program self-adaptive mutation rates (0 = fixed rate)
+    #[arg(long)]
+    self_adaptation: Option<f64>,
+
+    /// Tune the global mutation rate during the run (incompatible with --self-adaptation)
+    #[arg(long, value_enum)]
+    adaptive_mutation: Option<AdaptiveMutation>,
+
+    /// Probability per mutation of inserting or deleting one instruction
+    #[arg(long)]
+    indel_rate: Option<f64>,
+
+    /// Mutation operators applied to each child
+    #[arg(long, value_enum, value_name = "OPERATOR")]
+    mutation: Option<MutationOperator>,
+
+    /// Probability per mutation of replacing a block of whole instructions, with --mutation block or both
+    #[arg(long)]
+    block_rate: Option<f64>,
+
+    /// Minimum program length
+    #[arg(long)]
+    min_len: Option<usize>,
+
+    /// Maximum program length
+    #[arg(long)]
+    max_len: Option<usize>,
+
+    /// Lengths favoured by the random initial population
+    #[arg(long, value_enum, value_name = "BIAS")]
+    init_length_bias: Option<LengthBias>,
+
+    /// Number of memory cells in the virtual machine
+    #[arg(long)]
+    memory_size: Option<i32>,
+
+    /// Maximum number of instructions executed per program
+    #[arg(long)]
+    max_steps: Option<usize>,
+
+    /// Halt when a taken branch targets a negative address (canonical SUBLEQ)
+    #[arg(long)]
+    halt_on_negative: bool,
+
+    /// Memory address used as the I/O port (conventionally memory-size - 1)
+    #[arg(long)]
+    io_port: Option<usize>,
+
+    /// Comma-separated values programs read from the I/O port
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    input: Option<Vec<i32>>,
+
+    /// Stop executing a program once it revisits an earlier machine state
+    #[arg(long)]
+    detect_cycles: bool,
+
+    /// One-instruction set the machine executes
+    #[arg(long, value_enum)]
+    isa: Option<InstructionSet>,
+
+    /// Wrap the program counter around memory so execution never falls off the end
+    #[arg(long)]
+    wrap_pc: bool,
+
+    /// Restart at address 0 when execution falls off the end, so programs stop only by halting or the step limit (no effect with --wrap-pc)
+    #[arg(long)]
+    no_fallthrough_halt: bool,
+
+    /// Ignore writes into the program's own cells, leaving only data memory writable
+    #[arg(long)]
+    protect_code: bool,
+
+    /// Address programs are loaded at and start executing from, wrapping around memory
+    #[arg(long, value_name = "ADDRESS")]
+    load_offset: Option<usize>,
+
+    /// What memory holds around the program; random memory is drawn from --seed, which a replay must repeat
+    #[arg(long, value_enum)]
+    memory_init: Option<MemoryInit>,
+
+    /// Comma-separated values repeated through memory with --memory-init pattern
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    memory_pattern: Option<Vec<i32>>,
+
+    /// Program file (- for stdin) placed in the initial population, saved as --format says; read as assembly with --format asm
+    #[arg(long, visible_alias = "seed-genome", value_name = "PROGRAM.json")]
+    seed_program: Option<PathBuf>,
+
+    /// Number of copies of the seed program placed in the initial population
+    #[arg(long, requires = "seed_program")]
+    seed_copies: Option<usize>,
+
+    /// Seed for the random number generator, for reproducible runs
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of islands the population is split into
+    #[arg(long)]
+    islands: Option<usize>,
+
+    /// Generations between migrations of the best individuals to the next island
+    #[arg(long, value_name = "N")]
+    migrate_every: Option<usize>,
+
+    /// Number of individuals each island sends per migration
+    #[arg(long)]
+    migrants: Option<usize>,
+
+    /// Strategy used to pick parents
+    #[arg(long, value_enum)]
+    selection: Option<Selection>,
+
+    /// Select on copied words, length and steps as separate objectives (NSGA-II) and print the final Pareto front
+    #[arg(long)]
+    multiobjective: bool,
+
+    /// Number of individuals competing to become each parent (1 = uniform random)
+    #[arg(long)]
+    tournament_size: Option<usize>,
+
+    /// Selection pressure of rank selection, from 1 (uniform) to 2 (strongest)
+    #[arg(long)]
+    rank_pressure: Option<f64>,
+
+    /// Number of best programs copied unchanged into the next generation
+    #[arg(long)]
+    elitism: Option<usize>,
+
+    /// Number of the weakest offspring replaced by random programs each generation
+    #[arg(long)]
+    immigrants: Option<usize>,
+
+    /// Fraction of the population replaced per iteration in steady-state mode
+    #[arg(long)]
+    gap: Option<f64>,
+
+    /// Which individuals offspring replace in steady-state mode
+    #[arg(long, value_enum)]
+    replacement: Option<Replacement>,
+
+    /// Operator used to recombine two parents
+    #[arg(long, value_enum)]
+    crossover: Option<Crossover>,
+
+    /// Breed each child by cloning and mutating one selected parent, without crossover
+    #[arg(long, conflicts_with = "crossover")]
+    no_crossover: bool,
+
+    /// Breed offspring in parallel (reproducible, but not the same trajectory as sequential breeding)
+    #[arg(long)]
+    parallel_breeding: bool,
+
+    /// Number of worker threads (default: one per core)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// How executed memory is scored
+    #[arg(long, value_enum)]
+    fitness: Option<FitnessMode>,
+
+    /// Fitness penalty per word of code for programs that have not copied themselves
+    #[arg(long, conflicts_with = "target")]
+    parsimony: Option<f64>,
+
+    /// Number of single-gene hill-climbing moves tried on each new child
+    #[arg(long, value_name = "K")]
+    local_search: Option<usize>,
+
+    /// Evolve programs whose final memory matches these comma-separated values instead of self-replicators
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true, conflicts_with = "fitness", group = "targets")]
+    target: Option<Vec<i32>>,
+
+    /// Like --target, with the values read from this file (- for stdin), separated by commas or whitespace
+    #[arg(long, value_name = "FILE", conflicts_with = "fitness", group = "targets")]
+    target_file: Option<PathBuf>,
+
+    /// Memory address the --target values are compared from
+    #[arg(long, requires = "targets", default_value_t = 0)]
+    target_offset: usize,
+
+    /// Memoize fitness scores by genome
+    #[arg(long)]
+    cache: bool,
+
+    /// Maximum number of genomes kept in the fitness cache (default: unbounded)
+    #[arg(long)]
+    cache_size: Option<usize>,
+
+    /// Write per-generation fitness statistics to this CSV file
+    #[arg(long, value_name = "FILE.csv")]
+    log_csv: Option<PathBuf>,
+
+    /// Stream per-generation statistics and the best genome to this JSON Lines file
+    #[arg(long, value_name = "FILE.jsonl")]
+    jsonl: Option<PathBuf>,
+
+    /// Number of random pairs compared to estimate diversity each generation
+    #[arg(long)]
+    diversity_samples: Option<usize>,
+
+    /// Checkpoint the population every this many generations
+    #[arg(long, value_name = "N")]
+    checkpoint_every: Option<usize>,
+
+    /// File checkpoints are written to (default: checkpoint.json)
+    #[arg(long, value_name = "FILE.json")]
+    checkpoint_file: Option<PathBuf>,
+
+    /// Encoding of saved programs and checkpoints, and of programs read by replay, --run, --debug and --seed-program
+    #[arg(long, value_enum)]
+    format: Option<FileFormat>,
+
+    /// Continue the run saved in this checkpoint
+    #[arg(long, value_name = "CHECKPOINT")]
+    resume: Option<PathBuf>,
+
+    /// Save the best distinct genomes seen during the run, and every solution found, to this JSON file
+    #[arg(long, value_name = "FILE.json")]
+    hall_of_fame: Option<PathBuf>,
+
+    /// Number of genomes kept in the hall of fame
+    #[arg(long)]
+    hall_of_fame_size: Option<usize>,
+
+    /// Number of cells in the soup arena (soup mode)
+    #[arg(long)]
+    soup_size: Option<usize>,
+
+    /// Instructions each organism executes per turn (soup mode)
+    #[arg(long)]
+    soup_slice: Option<usize>,
+
+    /// Record every individual's parents and print the replicator's line of descent
+    #[arg(long)]
+    track_lineage: bool,
+
+    /// Save the replicator's full ancestry to this CSV file (requires --track-lineage)
+    #[arg(long, value_name = "FILE.csv", requires = "track_lineage")]
+    export_lineage: Option<PathBuf>,
+
+    /// Write the replicator, or else the best program, as plain SUBLEQ integers for other interpreters, with a header listing this machine's conventions
+    #[arg(long, value_name = "FILE.sq")]
+    export_subleq: Option<PathBuf>,
+
+    /// Write the mean, standard deviation, mode and convergence of each gene position across the final population to this CSV file
+    #[arg(long, value_name = "FILE.csv")]
+    dump_genes: Option<PathBuf>,
+
+    /// Only report warnings and results, not progress (same as RUST_LOG=warn)
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also report per-generation statistics and run events (same as RUST_LOG=debug)
+    #[arg(long)]
+    verbose: bool,
+
+    /// Print a bar chart of the population's program lengths every this many generations
+    #[arg(long, value_name = "G")]
+    report_every: Option<usize>,
+
+    /// Repeat the run this many times with consecutive seeds and report statistics across them; --log-csv, --jsonl, --checkpoint-file and --hall-of-fame get the seed before the extension, e.g. log.seed8.csv
+    #[arg(long, value_name = "N", conflicts_with_all = ["debug", "run", "resume", "cluster", "output", "export_lineage", "export_subleq", "dump_genes", "summary_json"])]
+    runs: Option<usize>,
+
+    /// Write the end-of-run summary to this JSON file
+    #[arg(long, value_name = "FILE.json")]
+    summary_json: Option<PathBuf>,
+
+    /// Group the final population into clusters of similar programs and report each
+    #[arg(long)]
+    cluster: bool,
+
+    /// Largest edit distance from a cluster's fittest program to its other members
+    #[arg(long, value_name = "EDITS", requires = "cluster", default_value_t = 8)]
+    cluster_radius: usize,
+
+    /// Save the clusters, with their member genomes, to this JSON file
+    #[arg(long, value_name = "FILE.json", requires = "cluster")]
+    cluster_json: Option<PathBuf>,
+
+    /// Show the final memory of each reported program as a grid marking the original and its copies
+    #[arg(long, global = true)]
+    dump_memory: bool,
+
+    /// Draw best, mean and median fitness per generation to this PNG file at the end of the run
+    #[cfg(feature = "plot")]
+    #[arg(long, value_name = "FILE.png")]
+    plot: Option<PathBuf>,
 }
 
-impl SUBLEQProgram {
-    fn new(length: usize) -> Self {
-        let mut rng = rand::thread_rng();
-        SUBLEQProgram {
-            code: (0..length).map(|_| rng.gen_range(-MEMORY_SIZE..MEMORY_SIZE)).collect(),
-        }
+#[derive(Subcommand)]
+enum Command {
+    /// Re-execute a program saved with --output and report how it ran, instead of
+    /// evolving; machine flags must come before the subcommand and match the original run
+    Replay {
+        /// Program file written by --output, or - for stdin; read as assembly with --format asm
+        #[arg(value_name = "PROGRAM")]
+        program: PathBuf,
+
+        /// Also print the instruction-by-instruction trace
+        #[arg(long)]
+        trace: bool,
+    },
+}
+
+fn fail(message: String) -> ! {
+    Args::command().error(clap::error::ErrorKind::ValueValidation, message).exit()
+}
+
+/// Reads the file at `path`, or all of stdin if it is `-`.
+fn read_input(path: &Path) -> Vec<u8> {
+    if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes).unwrap_or_else(|e| fail(format!("cannot read stdin: {}", e)));
+        bytes
+    } else {
+        fs::read(path).unwrap_or_else(|e| fail(format!("cannot read {}: {}", path.display(), e)))
     }
+}
 
-    fn mutate(&mut self) {
-        let mut rng = rand::thread_rng();
-        for gene in &mut self.code {
-            if rng.gen::<f64>() < MUTATION_RATE {
-                *gene = rng.gen_range(-MEMORY_SIZE..MEMORY_SIZE);
-            }
-        }
+/// [`read_input`] as text.
+fn read_input_text(path: &Path) -> String {
+    String::from_utf8(read_input(path)).unwrap_or_else(|_| fail(format!("{} is not valid UTF-8", input_name(path))))
+}
+
+/// How errors name the input at `path`.
+fn input_name(path: &Path) -> String {
+    if path == Path::new("-") { String::from("stdin") } else { path.display().to_string() }
+}
+
+/// Reads target values separated by commas or whitespace from `path` or stdin.
+fn read_target(path: &Path) -> Vec<i32> {
+    let text = read_input_text(path);
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse().unwrap_or_else(|_| fail(format!("invalid target value \"{}\" in {}", value, input_name(path)))))
+        .collect()
+}
+
+/// Reads a program from `path` or stdin: assembly with `format` asm, otherwise a program
+/// saved in either encoding.
+fn load(path: &Path, vm: &VmConfig, format: FileFormat) -> SUBLEQProgram {
+    let program = if format == FileFormat::Asm {
+        assemble(&read_input_text(path)).unwrap_or_else(|e| fail(format!("{}: {}", input_name(path), e)))
+    } else {
+        SUBLEQProgram::parse_program(&read_input(path), Path::new(&input_name(path)), vm.memory_size).unwrap_or_else(|e| fail(e.to_string()))
+    };
+    if program.code().len() > vm.memory_size as usize {
+        fail(format!("program has {} words but memory holds only {}", program.code().len(), vm.memory_size));
     }
+    program
+}
 
-    fn execute(&self) -> (Vec<i32>, usize) {
-        let mut memory = vec![0; MEMORY_SIZE as usize];
-        memory[..self.code.len()].copy_from_slice(&self.code);
-        
-        let mut pc = 0;
-        let mut steps = 0;
-        while pc < memory.len() - 2 && steps < MAX_EXECUTION_STEPS {
-            let a = memory[pc].rem_euclid(MEMORY_SIZE as i32) as usize;
-            let b = memory[pc + 1].rem_euclid(MEMORY_SIZE as i32) as usize;
-            let c = memory[pc + 2].rem_euclid(MEMORY_SIZE as i32) as usize;
-            
-            if a < memory.len() && b < memory.len() {
-                memory[a] = memory[a].wrapping_sub(memory[b]);
-                if memory[a] <= 0 {
-                    pc = c % memory.len();
-                } else {
-                    pc += 3;
-                }
-            } else {
-                pc += 3;
-            }
-            steps += 1;
+fn save(program: &SUBLEQProgram, path: &Path, vm: &VmConfig, format: FileFormat) {
+    let saved = match format {
+        FileFormat::Json => program.save_program(path, vm.memory_size),
+        FileFormat::Asm => program.save_program_asm(path, vm.memory_size, vm.isa),
+        #[cfg(feature = "bincode")]
+        FileFormat::Bincode => program.save_program_bin(path, vm.memory_size),
+        #[cfg(not(feature = "bincode"))]
+        FileFormat::Bincode => unreachable!("GaConfig::validate rejects bincode without the bincode feature"),
+    };
+    saved.unwrap_or_else(|e| fail(e.to_string()));
+}
+
+fn debug(path: &Path, vm: &VmConfig, format: FileFormat) {
+    let (trace, result) = load(path, vm, format).execute_trace(vm);
+    print_trace(&trace, vm.isa);
+    println!("Steps taken: {}", result.steps);
+    println!("Halted: {}, memory writes: {} ({} into its own code), cycle detected: {}", result.halted, result.writes, result.self_mod_writes, result.cycled);
+}
+
+/// Reruns a saved program on `vm`, which reproduces the original run's result exactly
+/// since execution is deterministic.
+fn replay(path: &Path, vm: &VmConfig, format: FileFormat, trace: bool, dump_memory: bool) {
+    let program = load(path, vm, format);
+    println!("Program ({} words): {:?}", program.code().len(), program.code());
+    let result = if trace {
+        let (trace, result) = program.execute_trace(vm);
+        print_trace(&trace, vm.isa);
+        result
+    } else {
+        program.execute(vm)
+    };
+    report(&program, &result, vm, dump_memory);
+    println!("Cycle detected: {}", result.cycled);
+    println!("Replication verified: {}", program.verify_replication(vm));
+}
+
+fn print_trace(trace: &[StepRecord], isa: InstructionSet) {
+    println!("{:>6} {:>5}  {:<24} effect", "step", "pc", "instruction");
+    for (step, record) in trace.iter().enumerate() {
+        let instruction = format!("{} {}, {}, {}", isa.mnemonic(), record.a, record.b, record.c);
+        let mut effect = match (record.written, record.output) {
+            (Some(value), _) => format!("mem[{}] = {}", record.a, value),
+            (None, Some(value)) => format!("output {}", value),
+            (None, None) => String::from("no effect"),
+        };
+        if record.next_pc != record.pc + 3 {
+            effect.push_str(&format!(", jump to {}", record.next_pc));
         }
-        (memory, steps)
+        println!("{:>6} {:>5}  {:<24} {}", step, record.pc, instruction, effect);
     }
+}
 
-    fn fitness(&self) -> usize {
-        let (result, steps) = self.execute();
-        let mut max_copies = 0;
-        for i in 0..result.len() - self.code.len() {
-            let mut correct = 0;
-            for (j, &val) in self.code.iter().enumerate() {
-                if result[i + j] == val {
-                    correct += 1;
-                } else {
-                    break;
-                }
-            }
-            max_copies = max_copies.max(correct);
-        }
-        // Reward full copies, shorter programs, and fewer execution steps
-        if max_copies == self.code.len() {
-            max_copies * 1000 / (self.code.len() * steps.max(1))
-        } else {
-            max_copies
-        }
+fn report(program: &SUBLEQProgram, result: &ExecutionResult, vm: &VmConfig, dump_memory: bool) {
+    print!("{}", disassemble(program.code(), vm.memory_size, vm.isa));
+    if dump_memory {
+        print_memory(&result.memory, program.code(), vm.load_offset);
+    } else {
+        println!("Execution result: {:?}", result.memory);
+    }
+    println!("Steps taken: {}", result.steps);
+    println!("Halted: {}, memory writes: {} ({} into its own code)", result.halted, result.writes, result.self_mod_writes);
+    if vm.io_port.is_some() {
+        println!("Output: {:?}", result.output);
     }
+}
 
-    fn verify_replication(&self) -> bool {
-        let (result, _) = self.execute();
-        for i in self.code.len()..result.len() - self.code.len() {
-            if self.code == result[i..i + self.code.len()] {
-                return true;
-            }
+fn print_lineage(lineage: &Lineage, replicator: &SUBLEQProgram) {
+    let ancestry = lineage.ancestry(replicator.id());
+    let oldest = ancestry.last().map_or(0, |(_, record)| record.generation);
+    println!("Ancestry: {} individuals back to generation {}", ancestry.len(), oldest);
+    println!("Line of descent through first parents:");
+    for (id, record) in lineage.line_of_descent(replicator.id()) {
+        match record.parents {
+            Some((a, b)) => println!("  generation {:>5}: #{} (parents #{}, #{})", record.generation, id, a, b),
+            None => println!("  generation {:>5}: #{} (initial population)", record.generation, id),
         }
-        false
     }
 }
 
-fn crossover(a: &SUBLEQProgram, b: &SUBLEQProgram) -> SUBLEQProgram {
-    let mut rng = rand::thread_rng();
-    let min_len = a.code.len().min(b.code.len());
-    let max_len = a.code.len().max(b.code.len());
-    let split = rng.gen_range(0..min_len);
-    let child_len = rng.gen_range(min_len..=max_len);
+fn print_clusters(clusters: &[Cluster], radius: usize) {
+    println!("Clusters within {} edits: {}", radius, clusters.len());
+    println!("{:>7} {:>7} {:>8} {:>7}  representative", "cluster", "size", "distinct", "best");
+    for (i, cluster) in clusters.iter().enumerate().take(CLUSTERS_SHOWN) {
+        println!("{:>7} {:>7} {:>8} {:>7}  {:?}", i, cluster.size, cluster.members.len(), cluster.best_fitness, cluster.representative);
+    }
+    if let Some(rest) = clusters.get(CLUSTERS_SHOWN..).filter(|rest| !rest.is_empty()) {
+        println!("  ... and {} smaller clusters of {} programs", rest.len(), rest.iter().map(|c| c.size).sum::<usize>());
+    }
+}
+
+fn print_pareto_front(front: &[(SUBLEQProgram, Objectives)]) {
+    println!("Pareto front of the final population: {} trade-offs", front.len());
+    println!("{:>7} {:>7} {:>7}  program", "copied", "length", "steps");
+    for (program, objectives) in front {
+        println!("{:>7} {:>7} {:>7}  {:?}", objectives.copied, objectives.length, objectives.steps, program.code());
+    }
+}
+
+fn print_summary(summary: &RunSummary) {
+    println!("Summary:");
+    println!("  generations evaluated: {}", summary.generations);
+    println!("  elapsed: {:.2}s", summary.elapsed_secs);
+    println!("  best fitness: {}", summary.best_fitness);
+    println!("  best genome: {:?}", summary.best_genome);
+    println!(
+        "  final generation fitness: mean {:.2}, median {}, std {:.2}",
+        summary.final_mean_fitness, summary.final_median_fitness, summary.final_std_fitness
+    );
+    println!("  distinct genotypes: {}", summary.unique_genotypes);
+    match summary.first_full_copy {
+        Some(generation) => println!("  first full copy: generation {}", generation),
+        None => println!("  first full copy: none"),
+    }
+    if let Some(accepted) = summary.accepted_worse {
+        println!("  worse mutants accepted: {}", accepted);
+    }
+    if let Some(hit_rate) = summary.cache_hit_rate {
+        println!("  fitness cache hit rate: {:.1}%", hit_rate * 100.0);
+    }
+}
 
-    let mut child = SUBLEQProgram { code: vec![0; child_len] };
-    
-    // Copy from first parent up to split point
-    child.code[..split].copy_from_slice(&a.code[..split]);
-    
-    // Copy from second parent after split point, up to the minimum length
-    let remaining = min_len - split;
-    child.code[split..min_len].copy_from_slice(&b.code[split..min_len]);
-    
-    // If child is longer than min_len, fill the rest randomly
-    if child_len > min_len {
-        for i in min_len..child_len {
-            child.code[i] = rng.gen_range(-MEMORY_SIZE..MEMORY_SIZE);
+/// Prints `memory` as rows of [`DUMP_COLUMNS`] cells, each prefixed with `O` if it holds
+/// the original program, `C` if it holds one of the copies the copies fitness counts,
+/// or left blank; zero cells elsewhere print as `.` so written ones stand out.
+///
+/// The memory is rotated as in [`ExecutionResult`], so row labels add `load_offset`
+/// back to show real addresses.
+fn print_memory(memory: &[i32], code: &[i32], load_offset: usize) {
+    let report = ReplicationReport::new(memory, code);
+    let copies = &report.copies;
+    let mut marks = vec![' '; memory.len()];
+    marks[..code.len()].fill('O');
+    for &start in copies {
+        marks[start..start + code.len()].fill('C');
+    }
+    let width = memory.iter().map(|cell| cell.to_string().len()).max().unwrap_or(1);
+    let label_width = memory.len().saturating_sub(1).to_string().len();
+    println!("Final memory (O original, C copy, . zero):");
+    for (row, cells) in memory.chunks(DUMP_COLUMNS).enumerate() {
+        let start = row * DUMP_COLUMNS;
+        let mut line = format!("{:>label_width$} |", (start + load_offset) % memory.len());
+        for (i, &cell) in cells.iter().enumerate() {
+            let text = if cell == 0 && marks[start + i] == ' ' { String::from(".") } else { cell.to_string() };
+            line.push_str(&format!(" {}{:>width$}", marks[start + i], text));
         }
+        println!("{}", line);
     }
+    if !copies.is_empty() {
+        println!("Full copies after the original at {:?}, covering {:.1}% of memory", copies, report.coverage * 100.0);
+    } else if let Some(offset) = report.longest_partial_offset {
+        println!("No full copies after the original; longest partial copy: {} of {} words at {}", report.longest_partial, code.len(), offset);
+    } else {
+        println!("No full copies after the original");
+    }
+}
 
-    child
+/// Counts of `lengths` in at most `max_buckets` equally wide buckets, each paired with
+/// its lower bound.
+fn length_histogram(lengths: &[usize], max_buckets: usize) -> Vec<(usize, usize)> {
+    let (Some(&min), Some(&max)) = (lengths.iter().min(), lengths.iter().max()) else {
+        return Vec::new();
+    };
+    let bucket = (max - min + 1).div_ceil(max_buckets);
+    let mut counts = vec![0; (max - min) / bucket + 1];
+    for &length in lengths {
+        counts[(length - min) / bucket] += 1;
+    }
+    counts.into_iter().enumerate().map(|(i, count)| (min + i * bucket, count)).collect()
 }
 
-fn main() {
-    let mut rng = rand::thread_rng();
-    let mut population: Vec<SUBLEQProgram> = (0..POPULATION_SIZE)
-        .map(|_| SUBLEQProgram::new(rng.gen_range(MIN_PROGRAM_LENGTH..=MAX_PROGRAM_LENGTH)))
+/// Prints the validated `config`, the number of genomes it can reach, and rough peak
+/// memory and checkpoint sizes of a run of it.
+fn dry_run(config: &GaConfig) {
+    println!("Configuration: {:#?}", config);
+    // Genes take values in -memory_size..memory_size, so there are sum(values^length) genomes
+    let values = 2.0 * config.memory_size as f64;
+    let lengths = (config.max_len - config.min_len + 1) as f64;
+    let log_genomes = config.max_len as f64 * values.log10() + ((1.0 - values.powf(-lengths)) / (1.0 - 1.0 / values)).log10();
+    println!("Search space: {} values per gene, lengths {} to {}: about 10^{:.1} genomes", values, config.min_len, config.max_len, log_genomes);
+
+    let word = std::mem::size_of::<i32>() as u64;
+    let program = (std::mem::size_of::<SUBLEQProgram>() + config.max_len * std::mem::size_of::<i32>()) as u64;
+    let threads = if !cfg!(feature = "parallel") {
+        1
+    } else if config.threads == 0 {
+        std::thread::available_parallelism().map_or(1, |n| n.get()) as u64
+    } else {
+        config.threads as u64
+    };
+    // Every genome a run evaluates is new at worst; saturating, as generations may be
+    // set near usize::MAX to run until stopped
+    let evaluated = (config.population as u64).saturating_mul(config.generations as u64);
+    let mut parts = Vec::new();
+    if config.mode == Mode::Soup {
+        parts.push(("arena", config.soup_size as u64 * word));
+    } else if config.mode == Mode::Coevolution {
+        parts.push(("populations", 4 * config.population as u64 * program));
+    } else {
+        // The old generation is kept while the new one is bred
+        parts.push(("population", 2 * config.population as u64 * program));
+    }
+    // Each thread executes in its own scratch memory, which cycle detection snapshots
+    let executions = if config.detect_cycles { 2 } else { 1 };
+    parts.push(("VM memory", threads * executions * config.memory_size as u64 * word));
+    if config.cache {
+        // Each entry also costs the key's vector, the score and the LRU list links
+        let entries = config.cache_size.map_or(evaluated, |size| (size as u64).min(evaluated));
+        parts.push(("fitness cache", entries.saturating_mul(config.max_len as u64 * word + 64)));
+    }
+    if config.track_lineage {
+        parts.push(("lineage", evaluated.saturating_mul(std::mem::size_of::<(u64, LineageRecord)>() as u64 + 8)));
+    }
+    let total = parts.iter().fold(0u64, |total, &(_, bytes)| total.saturating_add(bytes));
+    let parts: Vec<String> = parts.iter().map(|&(name, bytes)| format!("{} {}", name, format_bytes(bytes))).collect();
+    println!("Estimated peak memory: {} ({})", format_bytes(total), parts.join(", "));
+
+    if let Some(every) = config.checkpoint_every {
+        // JSON writes each gene as its digits, a sign and a comma; bincode as a zigzag varint
+        let gene = match config.format {
+            FileFormat::Json => config.memory_size.to_string().len() as u64 + 2,
+            FileFormat::Asm => unreachable!("GaConfig::validate rejects assembly checkpoints"),
+            FileFormat::Bincode => match 2 * config.memory_size as u64 {
+                0..251 => 1,
+                251..65536 => 3,
+                _ => 5,
+            },
+        };
+        let bytes = config.population as u64 * (config.max_len as u64 * gene + 64);
+        println!("Checkpoints: up to {} each, every {} generations, to {}", format_bytes(bytes), every, config.checkpoint_file.display());
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1.
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < units.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+/// One line per [`length_histogram`] bucket of `lengths`, labelled by the lengths it
+/// covers, with a bar scaled so the fullest bucket spans [`CHART_WIDTH`].
+fn length_chart(lengths: &[usize]) -> Vec<String> {
+    let histogram = length_histogram(lengths, LENGTH_BUCKETS);
+    let most = histogram.iter().map(|&(_, count)| count).max().unwrap_or(0).max(1);
+    let max = lengths.iter().max().copied().unwrap_or(0);
+    let labels: Vec<String> = histogram
+        .iter()
+        .enumerate()
+        .map(|(i, &(from, _))| {
+            let to = histogram.get(i + 1).map_or(max, |&(next, _)| next - 1);
+            if to == from { from.to_string() } else { format!("{}-{}", from, to) }
+        })
         .collect();
+    let width = labels.iter().map(String::len).max().unwrap_or(0);
+    labels
+        .iter()
+        .zip(&histogram)
+        .map(|(label, &(_, count))| format!("  {:>width$} | {} {}", label, "#".repeat((count * CHART_WIDTH).div_ceil(most)), count))
+        .collect()
+}
 
-    for generation in 0..GENERATIONS {
-        let fitness_scores: Vec<usize> = population.par_iter().map(|p| p.fitness()).collect();
-        
-        let best_fitness = *fitness_scores.iter().max().unwrap();
-        let best_program = &population[fitness_scores.iter().position(|&r| r == best_fitness).unwrap()];
-        
-        println!("Generation {}: Best fitness = {}", generation, best_fitness);
-        if best_program.verify_replication() {
-            println!("Self-replicator found: {:?}", best_program.code);
-            let (result, steps) = best_program.execute();
-            println!("Execution result: {:?}", result);
-            println!("Steps taken: {}", steps);
-            return;
-        }
+/// Mean, median and range of a non-empty list of measurements.
+fn describe(values: &[usize]) -> String {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) { (sorted[mid - 1] + sorted[mid]) as f64 / 2.0 } else { sorted[mid] as f64 };
+    let mean = sorted.iter().sum::<usize>() as f64 / sorted.len() as f64;
+    format!("mean {:.1}, median {}, min {}, max {}", mean, median, sorted[0], sorted[sorted.len() - 1])
+}
 
-        let mut new_population = Vec::with_capacity(POPULATION_SIZE);
-        
-        while new_population.len() < POPULATION_SIZE {
-            let parent1 = &population[rng.gen_range(0..POPULATION_SIZE)];
-            let parent2 = &population[rng.gen_range(0..POPULATION_SIZE)];
-            let mut child = crossover(parent1, parent2);
-            child.mutate();
-            new_population.push(child);
+/// Repeats the run `runs` times with seeds counting up from the configured one (or a
+/// random one) and reports how often, and how quickly, each found a solution.
+/// Inserts `.seed<N>` before the extension, so each run of `--runs` keeps its own file.
+fn with_seed(path: &Path, seed: u64) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!(".seed{}", seed));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+fn run_experiment(config: GaConfig, runs: usize, make_fitness: impl Fn() -> Box<dyn Fitness>) {
+    let base_seed = config.seed.unwrap_or_else(rand::random);
+    let mut solved_in = Vec::new();
+    let mut best_fitness = Vec::new();
+    for i in 0..runs {
+        let seed = base_seed.wrapping_add(i as u64);
+        let mut builder = config.clone().into_builder().seed(seed);
+        if let Some(path) = &config.log_csv {
+            builder = builder.log_csv(with_seed(path, seed));
+        }
+        if let Some(path) = &config.log_jsonl {
+            builder = builder.log_jsonl(with_seed(path, seed));
+        }
+        builder = builder.checkpoint_file(with_seed(&config.checkpoint_file, seed));
+        if let Some(path) = &config.hall_of_fame {
+            builder = builder.hall_of_fame(with_seed(path, seed));
         }
+        let config = builder.build().unwrap_or_else(|e| fail(e.to_string()));
+        let stop_on_interrupt = |_| if INTERRUPTED.load(Ordering::Relaxed) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) };
+        let outcome = if matches!(config.mode, Mode::Hillclimb | Mode::Anneal) {
+            run_hill_climb(config, make_fitness(), stop_on_interrupt)
+        } else {
+            run_with_callback(config, make_fitness(), stop_on_interrupt)
+        };
+        let outcome = outcome.unwrap_or_else(|e| fail(e.to_string()));
+        if outcome.stop == StopReason::Interrupted {
+            warn!("Run {} of {} interrupted; reporting the {} runs completed", i + 1, runs, i);
+            break;
+        }
+        let generations = outcome.summary.generations;
+        if outcome.replicator.is_some() {
+            info!("Run {}/{} (seed {}): solved after {} generations", i + 1, runs, seed, generations);
+            solved_in.push(generations);
+        } else {
+            info!("Run {}/{} (seed {}): best fitness {} after {} generations", i + 1, runs, seed, outcome.best_fitness, generations);
+        }
+        best_fitness.push(outcome.best_fitness);
+    }
+
+    let completed = best_fitness.len();
+    if completed == 0 {
+        return;
+    }
+    println!("Across {} runs with seeds {} to {}:", completed, base_seed, base_seed.wrapping_add(completed as u64 - 1));
+    println!("  success rate: {}/{} ({:.1}%)", solved_in.len(), completed, 100.0 * solved_in.len() as f64 / completed as f64);
+    if !solved_in.is_empty() {
+        println!("  generations to solution: {}", describe(&solved_in));
+    }
+    println!("  best fitness: {}", describe(&best_fitness));
+}
+
+/// Sends progress to stderr through `log`, at the level set by RUST_LOG unless
+/// `--quiet` or `--verbose` overrides it; results stay on stdout.
+fn init_logging(args: &Args) {
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if args.quiet {
+        logger.filter_level(LevelFilter::Warn);
+    } else if args.verbose {
+        logger.filter_level(LevelFilter::Debug);
+    }
+    logger
+        .format(|buf, record| match record.level() {
+            Level::Info => writeln!(buf, "{}", record.args()),
+            level => writeln!(buf, "{}: {}", level.as_str().to_lowercase(), record.args()),
+        })
+        .init();
+}
+
+fn main() {
+    let args = Args::parse();
+    init_logging(&args);
+    let replayed = args.command.as_ref().map(|Command::Replay { program, .. }| program);
+    let inputs = [replayed, args.debug.as_ref(), args.run.as_ref(), args.seed_program.as_ref(), args.target_file.as_ref()];
+    if inputs.into_iter().flatten().filter(|path| path.as_path() == Path::new("-")).count() > 1 {
+        fail(String::from("only one input can be read from stdin"));
+    }
+    let target = args.target.clone().or_else(|| args.target_file.as_deref().map(read_target));
+    let mut builder = match &args.config {
+        Some(path) => GaConfig::load(path).unwrap_or_else(|e| fail(e.to_string())).into_builder(),
+        None => GaConfig::builder(),
+    };
+    if let Some(mode) = args.mode {
+        builder = builder.mode(mode);
+    }
+    if let Some(population) = args.population {
+        builder = builder.population(population);
+    }
+    if let Some(generations) = args.generations {
+        builder = builder.generations(generations);
+    }
+    if let Some(stall_generations) = args.stall_generations {
+        builder = builder.stall_generations(stall_generations);
+    }
+    if let Some(max_time) = args.max_time {
+        builder = builder.max_time(max_time);
+    }
+    if let Some(stall_trigger) = args.stall_trigger {
+        builder = builder.stall_trigger(stall_trigger);
+    }
+    if let Some(hypermutation_factor) = args.hypermutation_factor {
+        builder = builder.hypermutation_factor(hypermutation_factor);
+    }
+    if let Some(hypermutation_generations) = args.hypermutation_generations {
+        builder = builder.hypermutation_generations(hypermutation_generations);
+    }
+    if let Some(restarts) = args.restarts {
+        builder = builder.restarts(restarts);
+    }
+    if let Some(initial_temp) = args.initial_temp {
+        builder = builder.initial_temp(initial_temp);
+    }
+    if let Some(cooling) = args.cooling {
+        builder = builder.cooling(cooling);
+    }
+    if let Some(find) = args.find {
+        builder = builder.find(find);
+    }
+    if let Some(find_distance) = args.find_distance {
+        builder = builder.find_distance(find_distance);
+    }
+    if args.verify_all {
+        builder = builder.verify_all(true);
+    }
+    if let Some(mutation_rate) = args.mutation_rate {
+        builder = builder.mutation_rate(mutation_rate);
+    }
+    if let Some(self_adaptation) = args.self_adaptation {
+        builder = builder.self_adaptation(self_adaptation);
+    }
+    if let Some(adaptive_mutation) = args.adaptive_mutation {
+        builder = builder.adaptive_mutation(adaptive_mutation);
+    }
+    if let Some(indel_rate) = args.indel_rate {
+        builder = builder.indel_rate(indel_rate);
+    }
+    if let Some(operator) = args.mutation {
+        builder = builder.mutation_operator(operator);
+    }
+    if let Some(block_rate) = args.block_rate {
+        builder = builder.block_rate(block_rate);
+    }
+    if let Some(min_len) = args.min_len {
+        builder = builder.min_len(min_len);
+    }
+    if let Some(max_len) = args.max_len {
+        builder = builder.max_len(max_len);
+    }
+    if let Some(bias) = args.init_length_bias {
+        builder = builder.init_length_bias(bias);
+    }
+    if let Some(memory_size) = args.memory_size {
+        builder = builder.memory_size(memory_size);
+    }
+    if let Some(max_steps) = args.max_steps {
+        builder = builder.max_steps(max_steps);
+    }
+    if args.halt_on_negative {
+        builder = builder.halt_on_negative(true);
+    }
+    if let Some(io_port) = args.io_port {
+        builder = builder.io_port(io_port);
+    }
+    if let Some(input) = args.input {
+        builder = builder.input(input);
+    }
+    if args.detect_cycles {
+        builder = builder.detect_cycles(true);
+    }
+    if let Some(isa) = args.isa {
+        builder = builder.isa(isa);
+    }
+    if args.wrap_pc {
+        builder = builder.wrap_pc(true);
+    }
+    if args.no_fallthrough_halt {
+        builder = builder.restart_at_end(true);
+    }
+    if args.protect_code {
+        builder = builder.protect_code(true);
+    }
+    if let Some(load_offset) = args.load_offset {
+        builder = builder.load_offset(load_offset);
+    }
+    if let Some(memory_init) = args.memory_init {
+        builder = builder.memory_init(memory_init);
+    }
+    if let Some(memory_pattern) = args.memory_pattern {
+        builder = builder.memory_pattern(memory_pattern);
+    }
+    if let Some(seed_copies) = args.seed_copies {
+        builder = builder.seed_copies(seed_copies);
+    }
+    if let Some(seed) = args.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(islands) = args.islands {
+        builder = builder.islands(islands);
+    }
+    if let Some(migrate_every) = args.migrate_every {
+        builder = builder.migrate_every(migrate_every);
+    }
+    if let Some(migrants) = args.migrants {
+        builder = builder.migrants(migrants);
+    }
+    if let Some(selection) = args.selection {
+        builder = builder.selection(selection);
+    }
+    if args.multiobjective {
+        builder = builder.multiobjective(true);
+    }
+    if let Some(tournament_size) = args.tournament_size {
+        builder = builder.tournament_size(tournament_size);
+    }
+    if let Some(rank_pressure) = args.rank_pressure {
+        builder = builder.rank_pressure(rank_pressure);
+    }
+    if let Some(elitism) = args.elitism {
+        builder = builder.elitism(elitism);
+    }
+    if let Some(immigrants) = args.immigrants {
+        builder = builder.immigrants(immigrants);
+    }
+    if let Some(gap) = args.gap {
+        builder = builder.gap(gap);
+    }
+    if let Some(replacement) = args.replacement {
+        builder = builder.replacement(replacement);
+    }
+    if let Some(crossover) = args.crossover {
+        builder = builder.crossover(crossover);
+    }
+    if args.no_crossover {
+        builder = builder.no_crossover(true);
+    }
+    if args.parallel_breeding {
+        builder = builder.parallel_breeding(true);
+    }
+    if let Some(threads) = args.threads {
+        builder = builder.threads(threads);
+    }
+    if let Some(fitness) = args.fitness {
+        builder = builder.fitness(fitness);
+    }
+    if let Some(parsimony) = args.parsimony {
+        builder = builder.parsimony(parsimony);
+    }
+    if let Some(local_search) = args.local_search {
+        builder = builder.local_search(local_search);
+    }
+    if args.cache {
+        builder = builder.cache(true);
+    }
+    if let Some(cache_size) = args.cache_size {
+        builder = builder.cache_size(cache_size);
+    }
+    if let Some(path) = args.log_csv {
+        builder = builder.log_csv(path);
+    }
+    if let Some(path) = args.jsonl {
+        builder = builder.log_jsonl(path);
+    }
+    if let Some(diversity_samples) = args.diversity_samples {
+        builder = builder.diversity_samples(diversity_samples);
+    }
+    if let Some(checkpoint_every) = args.checkpoint_every {
+        builder = builder.checkpoint_every(checkpoint_every);
+    }
+    if let Some(path) = args.checkpoint_file {
+        builder = builder.checkpoint_file(path);
+    }
+    if let Some(format) = args.format {
+        builder = builder.format(format);
+    }
+    if let Some(path) = args.resume {
+        builder = builder.resume(path);
+    }
+    if let Some(path) = args.hall_of_fame {
+        builder = builder.hall_of_fame(path);
+    }
+    if let Some(hall_of_fame_size) = args.hall_of_fame_size {
+        builder = builder.hall_of_fame_size(hall_of_fame_size);
+    }
+    if args.track_lineage {
+        builder = builder.track_lineage(true);
+    }
+    if let Some(soup_size) = args.soup_size {
+        builder = builder.soup_size(soup_size);
+    }
+    if let Some(soup_slice) = args.soup_slice {
+        builder = builder.soup_slice(soup_slice);
+    }
+    let mut config = builder.build().unwrap_or_else(|e| fail(e.to_string()));
+    // Read once the memory size and format are settled, as replay and --run read theirs
+    if let Some(path) = &args.seed_program {
+        let program = load(path, &config.vm(), config.format);
+        config = config.into_builder().seed_program(program).build().unwrap_or_else(|e| fail(e.to_string()));
+    }
+    if args.dry_run {
+        dry_run(&config);
+        return;
+    }
+    let vm = config.vm();
+    let generations = config.generations;
+    let stall_generations = config.stall_generations.unwrap_or(0);
+    let max_time = config.max_time.unwrap_or_default();
+    let find = config.find;
+    let format = config.format;
 
-        population = new_population;
+    if let Some(Command::Replay { program, trace }) = &args.command {
+        replay(program, &vm, format, *trace, args.dump_memory);
+        return;
+    }
+    if let Some(path) = &args.debug {
+        debug(path, &vm, format);
+        return;
+    }
+    if let Some(path) = &args.run {
+        let program = load(path, &vm, format);
+        println!("Program: {:?}", program.code());
+        report(&program, &program.execute(&vm), &vm, args.dump_memory);
+        println!("Replication verified: {}", program.verify_replication(&vm));
+        return;
     }
 
-    println!("No perfect self-replicator found within {} generations", GENERATIONS);
+    if config.mode == Mode::Soup {
+        if args.runs.is_some() {
+            fail(String::from("--runs does not support soup mode"));
+        }
+        let soup = run_soup(&config);
+        let stats = soup.stats();
+        println!("Soup after {} rounds: {} organisms alive, {} births, {} deaths", soup.round(), soup.len(), stats.births, stats.deaths);
+        if let Some((genome, carriers)) = soup.dominant_genome() {
+            println!("Most common genome ({} organisms): {:?}", carriers, genome);
+            print!("{}", disassemble(&genome, vm.memory_size, vm.isa));
+        }
+        return;
+    }
+    let (fitness_mode, parsimony) = (config.fitness, config.parsimony);
+    let make_fitness = || -> Box<dyn Fitness> {
+        match target.clone() {
+            Some(target) => Box::new(HammingTargetFitness::new(target, args.target_offset)),
+            None => Box::new(ReplicationFitness::new(fitness_mode).with_parsimony(parsimony)),
+        }
+    };
+    let fitness = make_fitness();
+    if config.mode == Mode::Coevolution {
+        if args.runs.is_some() {
+            fail(String::from("--runs does not support coevolution mode"));
+        }
+        let outcome = run_coevolution(&config, fitness.as_ref()).unwrap_or_else(|e| fail(e.to_string()));
+        println!("Coevolution after {} generations", outcome.generations);
+        for (name, best) in [("host", &outcome.best_host), ("parasite", &outcome.best_parasite)] {
+            if let Some((program, score)) = best {
+                println!("Best {} ({} words, score {}): {:?}", name, program.code().len(), score, program.code());
+                print!("{}", disassemble(program.code(), vm.memory_size, vm.isa));
+            }
+        }
+        return;
+    }
+    let goal = if target.is_some() { "program matching the target" } else { "perfect self-replicator" };
+    if let Some(code) = &config.seed_program {
+        let seed = SUBLEQProgram::from_code(code.clone());
+        let copies = if matches!(config.mode, Mode::Hillclimb | Mode::Anneal) { 1 } else { config.seed_copies };
+        info!("Seed program ({} words, {} copies): fitness = {}", code.len(), copies, seed.evaluate(&vm, fitness.as_ref()));
+    }
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        warn!("Interrupted; stopping after this generation (press Ctrl-C again to quit immediately)");
+    })
+    .unwrap_or_else(|e| fail(format!("cannot install the Ctrl-C handler: {}", e)));
+    if let Some(runs) = args.runs {
+        if runs == 0 {
+            fail(String::from("--runs must be at least 1"));
+        }
+        run_experiment(config, runs, make_fitness);
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        return;
+    }
+    let (self_adaptation, islands) = (config.self_adaptation, config.islands);
+    let block_mutation = config.mutation_operator != MutationOperator::Point;
+    if args.report_every == Some(0) {
+        fail(String::from("--report-every must be at least 1"));
+    }
+    let mut hypermutation = false;
+    #[cfg(feature = "plot")]
+    let mut history = Vec::new();
+    #[cfg(feature = "tui")]
+    let mut dashboard = args.tui.then(|| tui::Dashboard::new(vm.memory_size, vm.isa));
+    let print_generation = |stats: GenerationStats| {
+        #[cfg(feature = "plot")]
+        if args.plot.is_some() {
+            // Per-program lengths are not plotted and would dominate the memory kept
+            history.push(GenerationStats { lengths: Vec::new(), ..stats.clone() });
+        }
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            return ControlFlow::Break(());
+        }
+        #[cfg(feature = "tui")]
+        if let Some(dashboard) = &mut dashboard {
+            return dashboard.update(stats);
+        }
+        if args.report_every.is_some_and(|every| stats.generation.is_multiple_of(every)) {
+            info!("Generation {} lengths:", stats.generation);
+            for line in length_chart(&stats.lengths) {
+                info!("{}", line);
+            }
+        } else if stats.generation == 0 {
+            let buckets: Vec<String> =
+                length_histogram(&stats.lengths, LENGTH_BUCKETS).iter().map(|(from, count)| format!("{}+: {}", from, count)).collect();
+            info!("Generation 0 lengths: {}", buckets.join(", "));
+        }
+        if stats.hypermutation != hypermutation {
+            hypermutation = stats.hypermutation;
+            let state = if hypermutation { "activated" } else { "deactivated" };
+            info!("Hypermutation {} after generation {}", state, stats.generation);
+        }
+        let mut line = format!(
+            "Generation {}: Best fitness = {}, diversity = {:.3}, entropy = {:.3}",
+            stats.generation, stats.best_fitness, stats.diversity, stats.entropy
+        );
+        if self_adaptation > 0.0 {
+            line.push_str(&format!(", mean mutation rate = {:.4}", stats.mean_mutation_rate));
+        }
+        if let Some(rate) = stats.adaptive_rate {
+            line.push_str(&format!(", mutation rate = {:.4}", rate));
+        }
+        if parsimony > 0.0 {
+            line.push_str(&format!(", mean length = {:.1}", stats.mean_length));
+        }
+        if let Some(verified) = stats.verified {
+            line.push_str(&format!(", verified = {}", verified));
+        }
+        if let (Some(temperature), Some(accepted)) = (stats.temperature, stats.accepted_worse) {
+            line.push_str(&format!(", temperature = {:.3}, worse accepted = {}", temperature, accepted));
+        }
+        if block_mutation {
+            line.push_str(&format!(", block mutations = {}", stats.block_mutations));
+        }
+        if islands > 1 {
+            line.push_str(&format!(", island best = {:?}", stats.island_best));
+        }
+        info!("{}", line);
+        debug!(
+            "Generation {}: mean fitness = {:.2}, median = {}, std = {:.2}, min = {}, distinct genotypes = {}, immigrants = {}",
+            stats.generation, stats.mean_fitness, stats.median_fitness, stats.std_fitness, stats.min_fitness, stats.unique_genotypes, stats.immigrants
+        );
+        ControlFlow::Continue(())
+    };
+    let outcome = if matches!(config.mode, Mode::Hillclimb | Mode::Anneal) {
+        run_hill_climb(config, fitness, print_generation)
+    } else {
+        run_with_callback(config, fitness, print_generation)
+    };
+    #[cfg(feature = "tui")]
+    drop(dashboard);
+    let outcome = outcome.unwrap_or_else(|e| fail(e.to_string()));
+    let exported = outcome.replicator.clone().or_else(|| outcome.best.clone());
+    match outcome.replicator.zip(outcome.replicator_result) {
+        Some((replicator, result)) if target.is_some() => {
+            println!("Program matching the target found: {:?}", replicator.code());
+            report(&replicator, &result, &vm, args.dump_memory);
+            if let Some(path) = &args.output {
+                save(&replicator, path, &vm, format);
+            }
+        }
+        Some((replicator, result)) => {
+            println!("Self-replicator found ({} words): {:?}", replicator.code().len(), replicator.code());
+            report(&replicator, &result, &vm, args.dump_memory);
+            let minimized = minimize(&replicator, &vm);
+            println!("Minimized replicator ({} words): {:?}", minimized.code().len(), minimized.code());
+            print!("{}", disassemble(minimized.code(), vm.memory_size, vm.isa));
+            if let Some(path) = &args.output {
+                save(&replicator, path, &vm, format);
+            }
+            if let Some(lineage) = &outcome.lineage {
+                print_lineage(lineage, &replicator);
+                if let Some(path) = &args.export_lineage {
+                    lineage
+                        .save_ancestry(replicator.id(), path)
+                        .unwrap_or_else(|e| fail(format!("cannot write {}: {}", path.display(), e)));
+                }
+            }
+        }
+        None if outcome.stop == StopReason::Stalled => {
+            println!(
+                "Stopped early: best fitness {} has not improved for {} generations (last improvement in generation {})",
+                outcome.best_fitness, stall_generations, outcome.last_improvement
+            );
+            if let Some(best) = &outcome.best {
+                println!("Best program found: {:?}", best.code());
+                report(best, &best.execute(&vm), &vm, args.dump_memory);
+            }
+        }
+        None if outcome.stop == StopReason::TimeLimit => {
+            println!("Time budget of {:?} used up after {} generations", max_time, outcome.summary.generations);
+            if let Some(best) = &outcome.best {
+                println!("Best program found (fitness {}): {:?}", outcome.best_fitness, best.code());
+                report(best, &best.execute(&vm), &vm, args.dump_memory);
+            }
+        }
+        None if outcome.stop == StopReason::Interrupted => {
+            println!("Stopped by user with best fitness {}", outcome.best_fitness);
+            if let Some(best) = &outcome.best {
+                let default = match format {
+                    FileFormat::Json => "best.json",
+                    FileFormat::Bincode => "best.bin",
+                    FileFormat::Asm => "best.asm",
+                };
+                let path = args.output.clone().unwrap_or_else(|| PathBuf::from(default));
+                save(best, &path, &vm, format);
+                println!("Best program saved to {}", path.display());
+            }
+        }
+        None => println!("No {} found within {} generations", goal, generations),
+    }
+    if let (Some(path), Some(program)) = (&args.export_subleq, &exported) {
+        program.export_subleq(path, &vm).unwrap_or_else(|e| fail(e.to_string()));
+        println!("Program exported as SUBLEQ to {}", path.display());
+    }
+    if outcome.discoveries.len() > 1 {
+        println!("Distinct solutions found: {} of {}", outcome.discoveries.len(), find);
+        for (i, found) in outcome.discoveries.iter().enumerate() {
+            println!("  {}. generation {} ({} words): {:?}", i + 1, found.generation, found.program.code().len(), found.program.code());
+        }
+    }
+    if !outcome.pareto_front.is_empty() {
+        print_pareto_front(&outcome.pareto_front);
+    }
+    print_summary(&outcome.summary);
+    if args.cluster {
+        let clusters = cluster(&outcome.population, &outcome.fitness_scores, args.cluster_radius);
+        print_clusters(&clusters, args.cluster_radius);
+        if let Some(path) = &args.cluster_json {
+            let json = serde_json::to_string_pretty(&clusters).expect("clusters serialize");
+            fs::write(path, json + "\n").unwrap_or_else(|e| fail(format!("cannot write {}: {}", path.display(), e)));
+        }
+    }
+    if let Some(path) = &args.dump_genes {
+        save_gene_stats(&gene_stats(&outcome.population), path).unwrap_or_else(|e| fail(format!("cannot write {}: {}", path.display(), e)));
+    }
+    #[cfg(feature = "plot")]
+    if let Some(path) = &args.plot {
+        plot::plot_fitness(path, &history).unwrap_or_else(|e| fail(format!("cannot write {}: {}", path.display(), e)));
+    }
+    if let Some(path) = &args.summary_json {
+        let json = serde_json::to_string_pretty(&outcome.summary).expect("summary serializes");
+        fs::write(path, json + "\n").unwrap_or_else(|e| fail(format!("cannot write {}: {}", path.display(), e)));
+    }
+    if outcome.stop == StopReason::Interrupted {
+        process::exit(INTERRUPTED_EXIT_CODE);
+    }
 }
@@ -0,0 +1,72 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::stream_rng;
+
+/// What the cells around a program hold before it runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemoryInit {
+    /// Every cell starts at zero.
+    #[default]
+    Zero,
+    /// Every cell holds a random value in `-memory_size..memory_size`, the same for
+    /// every program run with the same seed.
+    Random,
+    /// Cells repeat `memory_pattern` from address 0.
+    Pattern,
+}
+
+impl MemoryInit {
+    /// The [`VmConfig::background`](crate::VmConfig::background) of a machine with
+    /// `memory_size` cells.
+    pub(crate) fn background(self, memory_size: i32, pattern: &[i32], seed: u64) -> Vec<i32> {
+        match self {
+            MemoryInit::Zero => Vec::new(),
+            MemoryInit::Random => {
+                // The main generator of a seeded run is stream 0, so this one never repeats its draws
+                let mut rng = stream_rng(seed, 1);
+                (0..memory_size).map(|_| rng.gen_range(-memory_size..memory_size)).collect()
+            }
+            MemoryInit::Pattern => pattern.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GaConfig, SUBLEQProgram, VmConfig};
+
+    /// Memory after a program that halts at once, run in a machine of `memory_size` cells
+    /// filled from `init`.
+    fn memory(init: MemoryInit, memory_size: i32, pattern: &[i32], seed: u64) -> Vec<i32> {
+        let vm = VmConfig {
+            halt_on_negative: true,
+            background: init.background(memory_size, pattern, seed),
+            ..GaConfig { memory_size, ..GaConfig::default() }.vm()
+        };
+        SUBLEQProgram::from_code(vec![0, 0, -1]).execute(&vm).memory
+    }
+
+    #[test]
+    fn zero_leaves_memory_blank() {
+        assert!(MemoryInit::Zero.background(16, &[], 3).is_empty());
+        assert_eq!(memory(MemoryInit::Zero, 16, &[], 3)[3..], [0; 13]);
+    }
+
+    #[test]
+    fn random_is_fixed_by_the_seed() {
+        let first = memory(MemoryInit::Random, 64, &[], 3);
+        assert_eq!(first[..3], [0, 0, -1]);
+        assert!(first.iter().all(|cell| (-64..64).contains(cell)));
+        assert!(first[3..].iter().any(|&cell| cell != 0));
+        assert_eq!(memory(MemoryInit::Random, 64, &[], 3), first);
+        assert_ne!(memory(MemoryInit::Random, 64, &[], 4), first);
+    }
+
+    #[test]
+    fn pattern_repeats_around_the_program() {
+        assert_eq!(memory(MemoryInit::Pattern, 8, &[1, 2, 3], 3), vec![0, 0, -1, 1, 2, 3, 1, 2]);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::vm::address;
+use crate::{SUBLEQProgram, VmConfig};
+
+/// Shrinks a self-replicator by greedily deleting words it does not need.
+///
+/// At each position the three words of an instruction are removed, or failing that a
+/// single word, first with operands that pointed past the gap shifted down to follow
+/// the code they referred to, then unchanged. A removal is kept if the shorter program still passes
+/// [`verify_replication`](SUBLEQProgram::verify_replication); passes repeat until
+/// none succeeds. Returns the program unchanged if it does not replicate.
+pub fn minimize(program: &SUBLEQProgram, vm: &VmConfig) -> SUBLEQProgram {
+    let mut best = program.clone();
+    if !best.verify_replication(vm) {
+        return best;
+    }
+    loop {
+        let mut shrunk = false;
+        let mut at = 0;
+        while at < best.code().len() {
+            let widths = [3, 1].into_iter().filter(|&width| at + width <= best.code().len());
+            match widths
+                .flat_map(|width| removals(best.code(), at, width, vm.memory_size))
+                .map(|code| SUBLEQProgram::from_code(code).with_mutation_rate(best.mutation_rate()))
+                .find(|candidate| candidate.verify_replication(vm))
+            {
+                Some(candidate) => {
+                    best = candidate;
+                    shrunk = true;
+                }
+                None => at += 1,
+            }
+        }
+        if !shrunk {
+            return best;
+        }
+    }
+}
+
+/// `code` without `width` words at `at`, with and without repaired references.
+fn removals(code: &[i32], at: usize, width: usize, memory_size: i32) -> Vec<Vec<i32>> {
+    let end = at + width;
+    if code.len() <= width {
+        return Vec::new();
+    }
+    let mut unchanged = code.to_vec();
+    unchanged.drain(at..end);
+    // References to the removed words themselves are left alone; the search decides
+    // whether the program survives without them
+    let repaired: Vec<i32> = unchanged
+        .iter()
+        .map(|&word| {
+            let target = address(word, memory_size);
+            if (end..=code.len()).contains(&target) {
+                word.wrapping_sub(width as i32)
+            } else {
+                word
+            }
+        })
+        .collect();
+    if repaired == unchanged {
+        vec![unchanged]
+    } else {
+        vec![repaired, unchanged]
+    }
+}
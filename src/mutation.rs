@@ -0,0 +1,102 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::Word;
+
+/// Most instructions a block mutation replaces at once.
+pub const MAX_BLOCK_INSTRUCTIONS: usize = 4;
+
+/// Which operators [`mutate`](crate::SUBLEQProgram::mutate) applies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MutationOperator {
+    /// Replace each gene independently with probability `mutation_rate`.
+    #[default]
+    Point,
+    /// With probability `block_rate`, replace a run of up to `MAX_BLOCK_INSTRUCTIONS`
+    /// whole instructions with random ones.
+    Block,
+    /// Point mutation, then block mutation.
+    Both,
+}
+
+impl MutationOperator {
+    pub(crate) fn point(self) -> bool {
+        self != MutationOperator::Block
+    }
+
+    pub(crate) fn block(self) -> bool {
+        self != MutationOperator::Point
+    }
+}
+
+/// Replaces between 1 and [`MAX_BLOCK_INSTRUCTIONS`] consecutive instructions of `code`
+/// with random ones, starting on an instruction boundary. Only whole instructions are
+/// replaced, so a trailing partial instruction is never touched, and the length stays
+/// the same. Returns false if `code` holds no whole instruction.
+pub(crate) fn block_mutate<W: Word>(code: &mut [W], memory_size: i32, rng: &mut impl Rng) -> bool {
+    let instructions = code.len() / 3;
+    if instructions == 0 {
+        return false;
+    }
+    let count = rng.gen_range(1..=MAX_BLOCK_INSTRUCTIONS.min(instructions));
+    let start = rng.gen_range(0..=instructions - count) * 3;
+    for gene in &mut code[start..start + count * 3] {
+        *gene = W::random(memory_size, rng);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+    use crate::testing::{proptest_config, valid_code};
+    use crate::{GaConfig, MutationConfig, SUBLEQProgram};
+
+    fn operator() -> impl Strategy<Value = MutationOperator> {
+        prop_oneof![Just(MutationOperator::Point), Just(MutationOperator::Block), Just(MutationOperator::Both)]
+    }
+
+    proptest! {
+        #![proptest_config(proptest_config())]
+
+        #[test]
+        fn mutate_stays_in_bounds(
+            code in valid_code(),
+            operator in operator(),
+            indel_rate in 0.0..=1.0f64,
+            block_rate in 0.0..=1.0f64,
+            seed: u64,
+        ) {
+            let config = GaConfig::default();
+            let params = MutationConfig { operator, indel_rate, block_rate, ..config.mutation() };
+            let mut rng = ChaCha12Rng::seed_from_u64(seed);
+            let mut program = SUBLEQProgram::from_code(code);
+            for _ in 0..10 {
+                program.mutate(&params, &mut rng);
+                prop_assert!((config.min_len..=config.max_len).contains(&program.code().len()));
+                prop_assert!(program.code().iter().all(|gene| (-config.memory_size..config.memory_size).contains(gene)));
+            }
+        }
+
+        #[test]
+        fn indels_keep_length_in_bounds(code in valid_code(), seed: u64) {
+            let config = GaConfig::default();
+            // Every mutation inserts or deletes, so programs keep hitting both bounds
+            let params = MutationConfig { indel_rate: 1.0, ..config.mutation() };
+            let mut rng = ChaCha12Rng::seed_from_u64(seed);
+            let mut program = SUBLEQProgram::from_code(code);
+            for _ in 0..200 {
+                let before = program.code().len();
+                program.mutate(&params, &mut rng);
+                let after = program.code().len();
+                prop_assert!((config.min_len..=config.max_len).contains(&after));
+                prop_assert!(after.abs_diff(before) == 0 || after.abs_diff(before) == 3);
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+#[cfg(not(feature = "parallel"))]
+pub(crate) use sequential::*;
+
+/// Stand-ins for the parts of rayon used by the crate, running everything on the
+/// calling thread, for targets such as plain WebAssembly that have no threads.
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    pub(crate) trait IntoParallelRefIterator<'a> {
+        type Iter: Iterator;
+
+        fn par_iter(&'a self) -> Self::Iter;
+    }
+
+    impl<'a, T: 'a> IntoParallelRefIterator<'a> for [T] {
+        type Iter = std::slice::Iter<'a, T>;
+
+        fn par_iter(&'a self) -> Self::Iter {
+            self.iter()
+        }
+    }
+
+    pub(crate) trait IntoParallelIterator: Iterator + Sized {
+        fn into_par_iter(self) -> Self {
+            self
+        }
+    }
+
+    impl<I: Iterator> IntoParallelIterator for I {}
+
+    pub(crate) trait IndexedParallelIterator: Iterator + Sized {
+        fn position_first(mut self, predicate: impl FnMut(Self::Item) -> bool) -> Option<usize> {
+            self.position(predicate)
+        }
+    }
+
+    impl<I: Iterator> IndexedParallelIterator for I {}
+
+    pub(crate) struct ThreadPool;
+
+    impl ThreadPool {
+        pub(crate) fn install<R>(&self, op: impl FnOnce() -> R) -> R {
+            op()
+        }
+    }
+}
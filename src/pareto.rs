@@ -0,0 +1,127 @@
+use crate::{count_copies, longest_prefix_match, SUBLEQProgram, VmConfig};
+
+/// The separate goals of multi-objective selection, measured from one execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Objectives {
+    /// Words of the program written after the original, to maximize: every word of
+    /// its full copies if it made any, else the longest partial copy.
+    pub copied: usize,
+    /// Program length in words, to minimize.
+    pub length: usize,
+    /// Instructions executed, to minimize.
+    pub steps: usize,
+}
+
+impl Objectives {
+    /// True if `self` is at least as good as `other` in every objective and better in one.
+    pub fn dominates(&self, other: &Objectives) -> bool {
+        let no_worse = self.copied >= other.copied && self.length <= other.length && self.steps <= other.steps;
+        no_worse && self != other
+    }
+}
+
+impl SUBLEQProgram {
+    /// Runs the program and measures each of its [`Objectives`].
+    pub fn objectives(&self, vm: &VmConfig) -> Objectives {
+        self.with_execution(vm, |result| {
+            let code = self.code();
+            let copied = match count_copies(result.memory, code, code.len()) {
+                0 => longest_prefix_match(result.memory, code, code.len()),
+                copies => copies * code.len(),
+            };
+            Objectives { copied, length: code.len(), steps: result.steps }
+        })
+    }
+}
+
+/// Front of each individual under non-dominated sorting: 0 for the Pareto front, 1 for
+/// the front left once that is removed, and so on.
+pub fn non_dominated_ranks(objectives: &[Objectives]) -> Vec<usize> {
+    let n = objectives.len();
+    // For each individual, the ones it dominates and the number that dominate it
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut dominators = vec![0usize; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            if objectives[i].dominates(&objectives[j]) {
+                dominated[i].push(j);
+                dominators[j] += 1;
+            } else if objectives[j].dominates(&objectives[i]) {
+                dominated[j].push(i);
+                dominators[i] += 1;
+            }
+        }
+    }
+    let mut ranks = vec![0; n];
+    let mut front: Vec<usize> = (0..n).filter(|&i| dominators[i] == 0).collect();
+    let mut rank = 0;
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            ranks[i] = rank;
+            for &j in &dominated[i] {
+                dominators[j] -= 1;
+                if dominators[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        front = next;
+        rank += 1;
+    }
+    ranks
+}
+
+/// Crowding distance of each individual within its front: the sum over objectives of
+/// the normalized gap between its neighbours, infinite at either end of a front.
+pub fn crowding_distances(objectives: &[Objectives], ranks: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; objectives.len()];
+    let fronts = ranks.iter().max().map_or(0, |&max| max + 1);
+    let measures: [fn(&Objectives) -> usize; 3] = [|o| o.copied, |o| o.length, |o| o.steps];
+    for rank in 0..fronts {
+        let mut members: Vec<usize> = (0..objectives.len()).filter(|&i| ranks[i] == rank).collect();
+        for measure in measures {
+            members.sort_by_key(|&i| measure(&objectives[i]));
+            let (low, high) = (measure(&objectives[members[0]]), measure(&objectives[members[members.len() - 1]]));
+            distances[members[0]] = f64::INFINITY;
+            distances[members[members.len() - 1]] = f64::INFINITY;
+            if high == low {
+                continue;
+            }
+            for window in members.windows(3) {
+                let gap = measure(&objectives[window[2]]) - measure(&objectives[window[0]]);
+                distances[window[1]] += gap as f64 / (high - low) as f64;
+            }
+        }
+    }
+    distances
+}
+
+/// Scores that order the population as NSGA-II's crowded comparison does: lower fronts
+/// first, and within a front the less crowded first. The best individual scores
+/// `objectives.len()`, the worst 1, so any selection scheme can use them as fitness.
+pub(crate) fn selection_scores(objectives: &[Objectives]) -> Vec<usize> {
+    let ranks = non_dominated_ranks(objectives);
+    let distances = crowding_distances(objectives, &ranks);
+    let mut order: Vec<usize> = (0..objectives.len()).collect();
+    order.sort_by(|&i, &j| ranks[i].cmp(&ranks[j]).then(distances[j].total_cmp(&distances[i])));
+    let mut scores = vec![0; objectives.len()];
+    for (position, &i) in order.iter().enumerate() {
+        scores[i] = objectives.len() - position;
+    }
+    scores
+}
+
+/// One program for each distinct trade-off on the Pareto front of `population`, the
+/// earliest of those sharing it, most copied first.
+pub(crate) fn pareto_front(population: &[SUBLEQProgram], objectives: &[Objectives]) -> Vec<(SUBLEQProgram, Objectives)> {
+    let ranks = non_dominated_ranks(objectives);
+    let mut front: Vec<(SUBLEQProgram, Objectives)> = Vec::new();
+    for (i, program) in population.iter().enumerate().filter(|&(i, _)| ranks[i] == 0) {
+        if !front.iter().any(|(_, point)| *point == objectives[i]) {
+            front.push((program.clone(), objectives[i]));
+        }
+    }
+    front.sort_by_key(|(_, objectives)| (std::cmp::Reverse(objectives.copied), objectives.length, objectives.steps));
+    front
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::path::Path;
+
+use plotters::prelude::*;
+use subleq_self_replicator::GenerationStats;
+
+/// One plotted quantity of a generation.
+type Measure = fn(&GenerationStats) -> f64;
+
+/// Draws best, mean and median fitness against generation as a PNG.
+///
+/// Both axes are scaled to the generations recorded, so a run that stopped early
+/// fills the chart just as a full-length one does.
+pub fn plot_fitness(path: &Path, history: &[GenerationStats]) -> Result<(), Box<dyn Error>> {
+    let first = history.first().map_or(0, |stats| stats.generation);
+    let last = history.last().map_or(0, |stats| stats.generation).max(first + 1);
+    let top = history.iter().map(|stats| stats.best_fitness as f64).fold(1.0, f64::max) * 1.05;
+
+    let root = BitMapBackend::new(path, (1024, 640)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Fitness over generations", ("sans-serif", 28))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(first..last, 0.0..top)?;
+    chart.configure_mesh().x_desc("Generation").y_desc("Fitness").draw()?;
+
+    let series: [(&str, RGBColor, Measure); 3] = [
+        ("best", BLUE, |stats| stats.best_fitness as f64),
+        ("mean", RED, |stats| stats.mean_fitness),
+        ("median", GREEN, |stats| stats.median_fitness),
+    ];
+    for (label, color, value) in series {
+        chart
+            .draw_series(
+                // Points keep a run that ended in its first generation visible
+                LineSeries::new(history.iter().map(|stats| (stats.generation, value(stats))), color.stroke_width(2)).point_size(2),
+            )?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw()?;
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::ops::ControlFlow;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::{run_hill_climb, run_with_callback, GaConfig, GenerationStats, Mode, ReplicationFitness, RunReport, SUBLEQProgram};
+
+/// Reads a config from a dict with the keys of a TOML experiment file, all optional.
+fn config_from(py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<GaConfig> {
+    let config = match config {
+        Some(dict) => {
+            let json: String = py.import("json")?.call_method1("dumps", (dict,))?.extract()?;
+            serde_json::from_str(&json).map_err(|e| PyValueError::new_err(format!("invalid config: {}", e)))?
+        }
+        None => GaConfig::default(),
+    };
+    config.validate().map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(config)
+}
+
+/// A SUBLEQ program; methods take an optional config dict for the machine and objective.
+#[pyclass(name = "SUBLEQProgram")]
+struct PyProgram(SUBLEQProgram);
+
+impl PyProgram {
+    /// Rejects programs that do not fit in the configured memory instead of panicking.
+    fn checked(&self, py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<GaConfig> {
+        let config = config_from(py, config)?;
+        if self.0.code().len() > config.memory_size as usize {
+            return Err(PyValueError::new_err(format!(
+                "program has {} words but memory holds only {}",
+                self.0.code().len(),
+                config.memory_size
+            )));
+        }
+        Ok(config)
+    }
+}
+
+#[pymethods]
+impl PyProgram {
+    #[new]
+    fn new(code: Vec<i32>) -> Self {
+        PyProgram(SUBLEQProgram::from_code(code))
+    }
+
+    #[getter]
+    fn code(&self) -> Vec<i32> {
+        self.0.code().to_vec()
+    }
+
+    /// Runs the program and returns a dict with the final `memory`, `steps`, `halted`,
+    /// `writes`, `self_modified`, `self_mod_writes`, `output` and `cycled`.
+    #[pyo3(signature = (config=None))]
+    fn execute<'py>(&self, py: Python<'py>, config: Option<&Bound<'py, PyDict>>) -> PyResult<Bound<'py, PyDict>> {
+        let result = self.0.execute(&self.checked(py, config)?.vm());
+        let dict = PyDict::new(py);
+        dict.set_item("memory", result.memory)?;
+        dict.set_item("steps", result.steps)?;
+        dict.set_item("halted", result.halted)?;
+        dict.set_item("writes", result.writes)?;
+        dict.set_item("self_modified", result.self_modified)?;
+        dict.set_item("self_mod_writes", result.self_mod_writes)?;
+        dict.set_item("output", result.output)?;
+        dict.set_item("cycled", result.cycled)?;
+        Ok(dict)
+    }
+
+    /// Score under the replication objective selected by the config's `fitness` and `parsimony`.
+    #[pyo3(signature = (config=None))]
+    fn fitness(&self, py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<usize> {
+        let config = self.checked(py, config)?;
+        Ok(self.0.evaluate(&config.vm(), &ReplicationFitness::new(config.fitness).with_parsimony(config.parsimony)))
+    }
+
+    #[pyo3(signature = (config=None))]
+    fn verify_replication(&self, py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<bool> {
+        Ok(self.0.verify_replication(&self.checked(py, config)?.vm()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SUBLEQProgram({:?})", self.0.code())
+    }
+}
+
+/// Per-generation `(generation, best, mean, median)` fitness.
+type Trajectory = Vec<(usize, usize, f64, f64)>;
+
+/// Evolves under `config` with the GIL released, handing each generation to `callback`.
+fn evolve(py: Python<'_>, config: GaConfig, callback: impl FnMut(GenerationStats) -> ControlFlow<()> + Send) -> PyResult<RunReport> {
+    let fitness = Box::new(ReplicationFitness::new(config.fitness).with_parsimony(config.parsimony));
+    let report = match config.mode {
+        Mode::Soup => return Err(PyValueError::new_err("soup mode is not supported from Python")),
+        Mode::Coevolution => return Err(PyValueError::new_err("coevolution mode is not supported from Python")),
+        Mode::Hillclimb | Mode::Anneal => py.detach(|| run_hill_climb(config, fitness, callback)),
+        Mode::Generational | Mode::SteadyState => py.detach(|| run_with_callback(config, fitness, callback)),
+    };
+    report.map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Evolves self-replicators under `config` and returns the best genome found.
+#[pyfunction]
+#[pyo3(signature = (config=None))]
+fn run(py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<Vec<i32>> {
+    let report = evolve(py, config_from(py, config)?, |_| ControlFlow::Continue(()))?;
+    Ok(report.summary.best_genome)
+}
+
+/// Like `run`, also returning one `(generation, best, mean, median)` fitness tuple per
+/// generation, ready for plotting.
+#[pyfunction]
+#[pyo3(signature = (config=None))]
+fn run_with_trajectory(py: Python<'_>, config: Option<&Bound<'_, PyDict>>) -> PyResult<(Vec<i32>, Trajectory)> {
+    let mut trajectory = Vec::new();
+    let report = evolve(py, config_from(py, config)?, |stats| {
+        trajectory.push((stats.generation, stats.best_fitness, stats.mean_fitness, stats.median_fitness));
+        ControlFlow::Continue(())
+    })?;
+    Ok((report.summary.best_genome, trajectory))
+}
+
+#[pymodule]
+#[pyo3(name = "subleq_self_replicator")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyProgram>()?;
+    module.add_function(wrap_pyfunction!(run, module)?)?;
+    module.add_function(wrap_pyfunction!(run_with_trajectory, module)?)?;
+    Ok(())
+}
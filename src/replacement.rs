@@ -0,0 +1,38 @@
+use serde::Deserialize;
+
+use crate::stats::edit_distance_within;
+use crate::SUBLEQProgram;
+
+/// Which individuals a steady-state step's offspring take the place of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Replacement {
+    /// Evict the worst `gap` fraction of the population.
+    #[default]
+    Worst,
+    /// Deterministic crowding: each child challenges the individual closest to it by
+    /// edit distance and replaces it only if it scores higher, so distinct niches
+    /// survive. Costs one distance per individual per child.
+    Crowding,
+}
+
+/// Index of the program in `population` closest to `code` by edit distance, skipping
+/// slots marked `taken`; ties go to the earliest slot.
+pub(crate) fn most_similar(population: &[SUBLEQProgram], taken: &[bool], code: &[i32]) -> Option<usize> {
+    let mut closest: Option<(usize, usize)> = None;
+    for (slot, program) in population.iter().enumerate().filter(|&(slot, _)| !taken[slot]) {
+        // Only a strictly closer program can displace the one found so far
+        let limit = match closest {
+            Some((_, 0)) => break,
+            Some((_, distance)) => distance - 1,
+            None => usize::MAX,
+        };
+        if program.code().len().abs_diff(code.len()) > limit {
+            continue;
+        }
+        if let Some(distance) = edit_distance_within(program.code(), code, limit) {
+            closest = Some((slot, distance));
+        }
+    }
+    closest.map(|(slot, _)| slot)
+}
@@ -0,0 +1,40 @@
+use crate::fitness::longest_prefix;
+use crate::{copy_offsets, SUBLEQProgram, VmConfig};
+
+/// Where a program copied itself in its final memory, as the replication objectives
+/// scan for it. Offsets are relative to the load address, as in
+/// [`ExecutionResult`](crate::ExecutionResult), and only memory after the original counts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicationReport {
+    /// Start offsets of the non-overlapping full copies, as [`copy_offsets`] finds them.
+    pub copies: Vec<usize>,
+    /// Length of the longest prefix of the program written anywhere, the whole program
+    /// if it made a full copy.
+    pub longest_partial: usize,
+    /// Start of the earliest `longest_partial` match; `None` if no word matched.
+    pub longest_partial_offset: Option<usize>,
+    /// Fraction of memory taken up by the full copies.
+    pub coverage: f64,
+}
+
+impl ReplicationReport {
+    /// Scans `memory` for copies of `code`, which was loaded at its start.
+    pub fn new(memory: &[i32], code: &[i32]) -> Self {
+        let copies = copy_offsets(memory, code, code.len());
+        let longest = longest_prefix(memory, code, code.len());
+        let coverage = if memory.is_empty() { 0.0 } else { (copies.len() * code.len()) as f64 / memory.len() as f64 };
+        ReplicationReport {
+            copies,
+            longest_partial: longest.map_or(0, |(length, _)| length),
+            longest_partial_offset: longest.map(|(_, offset)| offset),
+            coverage,
+        }
+    }
+}
+
+impl SUBLEQProgram {
+    /// Runs the program and reports where it copied itself.
+    pub fn replication_report(&self, vm: &VmConfig) -> ReplicationReport {
+        self.with_execution(vm, |result| ReplicationReport::new(result.memory, self.code()))
+    }
+}
@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "bincode")]
+use crate::binary::{self, Genome, PROGRAM_BIN_VERSION, PROGRAM_MAGIC};
+use crate::{disassemble, InstructionSet, SUBLEQProgram, VmConfig};
+
+/// Encoding of saved programs and checkpoints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileFormat {
+    /// Human-readable JSON.
+    #[default]
+    Json,
+    /// Compact bincode behind a magic header and layout version; needs the `bincode` feature.
+    Bincode,
+    /// SUBLEQ assembly, one instruction per line. It records no memory size, so it holds
+    /// programs but not checkpoints.
+    Asm,
+}
+
+/// On-disk form of a program, recording what it needs to be run as it was evolved.
+#[derive(Serialize, Deserialize)]
+struct SavedProgram<P> {
+    length: usize,
+    memory_size: i32,
+    #[serde(flatten)]
+    program: P,
+}
+
+/// Binary form of [`SavedProgram`], which bincode cannot read because of its flattened field.
+#[cfg(feature = "bincode")]
+#[derive(Serialize, Deserialize)]
+struct BinaryProgram {
+    memory_size: i32,
+    program: Genome,
+}
+
+impl SUBLEQProgram {
+    /// Writes the program as JSON, along with its length and the memory size it was evolved for.
+    pub fn save_program(&self, path: &Path, memory_size: i32) -> Result<(), ProgramFileError> {
+        let saved = SavedProgram { length: self.code().len(), memory_size, program: self };
+        let json = serde_json::to_string_pretty(&saved).expect("programs always serialize");
+        fs::write(path, json + "\n").map_err(|e| ProgramFileError::Write(path.to_path_buf(), e))
+    }
+
+    /// Writes the program in bincode behind [`PROGRAM_MAGIC`](crate::PROGRAM_MAGIC) and
+    /// [`PROGRAM_BIN_VERSION`](crate::PROGRAM_BIN_VERSION), along with the memory size it
+    /// was evolved for.
+    #[cfg(feature = "bincode")]
+    pub fn save_program_bin(&self, path: &Path, memory_size: i32) -> Result<(), ProgramFileError> {
+        let saved = BinaryProgram { memory_size, program: Genome::from(self) };
+        let bytes = binary::encode(PROGRAM_MAGIC, PROGRAM_BIN_VERSION, &saved);
+        fs::write(path, bytes).map_err(|e| ProgramFileError::Write(path.to_path_buf(), e))
+    }
+
+    /// Writes the program as [`disassemble`](crate::disassemble)d assembly, which
+    /// [`assemble`](crate::assemble) reads back.
+    pub fn save_program_asm(&self, path: &Path, memory_size: i32, isa: InstructionSet) -> Result<(), ProgramFileError> {
+        fs::write(path, disassemble(self.code(), memory_size, isa)).map_err(|e| ProgramFileError::Write(path.to_path_buf(), e))
+    }
+
+    /// Writes the program as [`to_subleq`](Self::to_subleq) text for other interpreters.
+    pub fn export_subleq(&self, path: &Path, vm: &VmConfig) -> Result<(), ProgramFileError> {
+        fs::write(path, self.to_subleq(vm)).map_err(|e| ProgramFileError::Write(path.to_path_buf(), e))
+    }
+
+    /// The program as whitespace-separated integers, one instruction per line, after a
+    /// `#` comment header describing the machine `vm` runs it on.
+    ///
+    /// The words are raw, not translated: the program reads and copies its own cells, so
+    /// rewriting operands for another interpreter's conventions would change what it
+    /// does. The header says where this machine differs from the usual SUBLEQ instead.
+    pub fn to_subleq(&self, vm: &VmConfig) -> String {
+        let (code, memory_size) = (self.code(), vm.memory_size);
+        let mut lines = vec![
+            format!("# SUBLEQ program of {} words, exported by subleq_self_replicator", code.len()),
+            format!("# Load at address {} of a {}-cell memory", vm.load_offset, memory_size),
+        ];
+        if !vm.background.is_empty() {
+            lines.push(String::from("# The rest of memory was not zero when evolved (memory_init), which is not recorded here"));
+        }
+        lines.push(String::from("# Each line is one instruction `a b c`, where this machine differs from standard SUBLEQ:"));
+        lines.push(match vm.isa {
+            InstructionSet::Subleq => String::from("#   mem[a] = mem[a] - mem[b], then jump to c if the result is <= 0 (a is the destination, not b)"),
+            InstructionSet::Subneg => String::from("#   mem[a] = mem[a] - mem[b], then jump to c if the result is < 0 (a is the destination, not b)"),
+            InstructionSet::Addleq => String::from("#   mem[a] = mem[a] + mem[b], then jump to c if the result is <= 0"),
+        });
+        lines.push(format!("#   every operand is taken modulo {} (rem_euclid), so -1 is address {}", memory_size, memory_size - 1));
+        lines.push(String::from(if vm.halt_on_negative {
+            "#   a taken jump to a negative c halts"
+        } else {
+            "#   negative jump targets wrap like other operands; no jump halts"
+        }));
+        lines.push(String::from(if vm.wrap_pc {
+            "#   the program counter wraps around the end of memory"
+        } else if vm.restart_at_end {
+            "#   running off the end of memory restarts at address 0"
+        } else {
+            "#   execution halts once an instruction would run past the end of memory"
+        }));
+        lines.push(format!("#   execution is cut off after {} instructions", vm.max_steps));
+        if let Some(port) = vm.io_port {
+            lines.push(format!("#   address {} is I/O: as b it reads the next input into a, halting once input runs out, and as a it outputs mem[b]; neither jumps", port));
+        }
+        if vm.protect_code {
+            lines.push(String::from("#   writes into the program's own cells are ignored"));
+        }
+        lines.push(String::from("#   words are 32-bit and arithmetic wraps on overflow"));
+        lines.extend(code.chunks(3).map(|instruction| instruction.iter().map(i32::to_string).collect::<Vec<_>>().join(" ")));
+        lines.join("\n") + "\n"
+    }
+
+    /// Reads a program written by [`save_program`](Self::save_program), or by
+    /// `save_program_bin` when the `bincode` feature is on, checking that it is intact and
+    /// was evolved for a machine with `memory_size` cells.
+    pub fn load_program(path: &Path, memory_size: i32) -> Result<SUBLEQProgram, ProgramFileError> {
+        let bytes = fs::read(path).map_err(|e| ProgramFileError::Read(path.to_path_buf(), e))?;
+        Self::parse_program(&bytes, path, memory_size)
+    }
+
+    /// [`load_program`](Self::load_program) for contents already read, such as from
+    /// stdin; `path` names their source in errors.
+    pub fn parse_program(bytes: &[u8], path: &Path, memory_size: i32) -> Result<SUBLEQProgram, ProgramFileError> {
+        #[cfg(feature = "bincode")]
+        if let Some(version) = binary::version(bytes, PROGRAM_MAGIC) {
+            if version != PROGRAM_BIN_VERSION {
+                return Err(ProgramFileError::BinaryVersion { path: path.to_path_buf(), version });
+            }
+            let saved: BinaryProgram = binary::decode(bytes).map_err(|e| ProgramFileError::Decode(path.to_path_buf(), e))?;
+            if saved.memory_size != memory_size {
+                return Err(ProgramFileError::MemoryMismatch { saved: saved.memory_size, current: memory_size });
+            }
+            return Ok(saved.program.into());
+        }
+        let text = std::str::from_utf8(bytes)
+            .map_err(|e| ProgramFileError::Read(path.to_path_buf(), io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let saved: SavedProgram<SUBLEQProgram> =
+            serde_json::from_str(text).map_err(|e| ProgramFileError::Parse(path.to_path_buf(), e))?;
+        if saved.length != saved.program.code().len() {
+            return Err(ProgramFileError::LengthMismatch { length: saved.length, words: saved.program.code().len() });
+        }
+        if saved.memory_size != memory_size {
+            return Err(ProgramFileError::MemoryMismatch { saved: saved.memory_size, current: memory_size });
+        }
+        Ok(saved.program)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProgramFileError {
+    Read(PathBuf, io::Error),
+    Write(PathBuf, io::Error),
+    Parse(PathBuf, serde_json::Error),
+    #[cfg(feature = "bincode")]
+    Decode(PathBuf, bincode::Error),
+    #[cfg(feature = "bincode")]
+    BinaryVersion { path: PathBuf, version: u32 },
+    LengthMismatch { length: usize, words: usize },
+    MemoryMismatch { saved: i32, current: i32 },
+}
+
+impl fmt::Display for ProgramFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProgramFileError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ProgramFileError::Write(path, e) => write!(f, "cannot write {}: {}", path.display(), e),
+            ProgramFileError::Parse(path, e) => write!(f, "invalid program {}: {}", path.display(), e),
+            #[cfg(feature = "bincode")]
+            ProgramFileError::Decode(path, e) => write!(f, "invalid binary program {}: {}", path.display(), e),
+            #[cfg(feature = "bincode")]
+            ProgramFileError::BinaryVersion { path, version } => write!(
+                f,
+                "binary program {} has layout version {}, but this build reads version {}",
+                path.display(),
+                version,
+                PROGRAM_BIN_VERSION
+            ),
+            ProgramFileError::LengthMismatch { length, words } => {
+                write!(f, "program declares length {} but has {} words", length, words)
+            }
+            ProgramFileError::MemoryMismatch { saved, current } => {
+                write!(f, "program was evolved for memory_size {} but the current memory_size is {}", saved, current)
+            }
+        }
+    }
+}
+
+impl Error for ProgramFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProgramFileError::Read(_, e) | ProgramFileError::Write(_, e) => Some(e),
+            ProgramFileError::Parse(_, e) => Some(e),
+            #[cfg(feature = "bincode")]
+            ProgramFileError::Decode(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{GaConfig, SUBLEQProgram};
+
+/// How parents are picked from the scored population.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Selection {
+    /// Fittest of `tournament_size` uniformly drawn candidates.
+    #[default]
+    Tournament,
+    /// Probability proportional to fitness.
+    Roulette,
+    /// Every individual equally likely.
+    Uniform,
+    /// Probability falling linearly with fitness rank, steepened by `rank_pressure`.
+    Rank,
+}
+
+/// Samples `k` individuals uniformly (with replacement) and returns the index of the fittest.
+///
+/// With `k == 1` this is plain uniform selection.
+pub fn tournament_select(population: &[SUBLEQProgram], fitnesses: &[usize], k: usize, rng: &mut impl Rng) -> usize {
+    let mut best = rng.gen_range(0..population.len());
+    for _ in 1..k {
+        let candidate = rng.gen_range(0..population.len());
+        if fitnesses[candidate] > fitnesses[best] {
+            best = candidate;
+        }
+    }
+    best
+}
+
+/// Picks an index with probability proportional to its fitness, or uniformly if every
+/// fitness is zero.
+///
+/// This builds the cumulative distribution on every call; use [`RouletteWheel`] to
+/// draw many parents from the same generation.
+pub fn roulette_select(fitnesses: &[usize], rng: &mut impl Rng) -> usize {
+    RouletteWheel::new(fitnesses).sample(rng)
+}
+
+/// Cumulative fitness distribution of one generation.
+pub struct RouletteWheel {
+    cumulative: Vec<u64>,
+}
+
+impl RouletteWheel {
+    pub fn new(fitnesses: &[usize]) -> Self {
+        let mut total = 0u64;
+        let cumulative = fitnesses
+            .iter()
+            .map(|&f| {
+                total += f as u64;
+                total
+            })
+            .collect();
+        RouletteWheel { cumulative }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        let total = self.cumulative.last().copied().unwrap_or(0);
+        if total == 0 {
+            return rng.gen_range(0..self.cumulative.len());
+        }
+        let target = rng.gen_range(0..total);
+        // First index whose running total exceeds the target; zero-fitness entries never match.
+        self.cumulative.partition_point(|&c| c <= target)
+    }
+}
+
+/// Linear rank selection probabilities of each individual, in population order.
+///
+/// Ranked from least to most fit, the individual of rank `i` out of `n` is picked with
+/// probability `(2 - s + 2 (s - 1) i / (n - 1)) / n` for a `pressure` `s` in `[1, 2]`,
+/// so only the ordering of the scores matters, never their scale. Equally fit
+/// individuals share the probabilities of the ranks they span.
+pub fn rank_probabilities(fitnesses: &[usize], pressure: f64) -> Vec<f64> {
+    let n = fitnesses.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| fitnesses[i]);
+    let rank_probability = |rank: usize| {
+        let slope = if n > 1 { 2.0 * (pressure - 1.0) * rank as f64 / (n - 1) as f64 } else { pressure - 1.0 };
+        (2.0 - pressure + slope) / n as f64
+    };
+    let mut probabilities = vec![0.0; n];
+    let mut start = 0;
+    for tied in order.chunk_by(|&a, &b| fitnesses[a] == fitnesses[b]) {
+        let shared = (start..start + tied.len()).map(rank_probability).sum::<f64>() / tied.len() as f64;
+        for &i in tied {
+            probabilities[i] = shared;
+        }
+        start += tied.len();
+    }
+    probabilities
+}
+
+/// Cumulative [`rank_probabilities`] of one generation.
+pub struct RankWheel {
+    cumulative: Vec<f64>,
+}
+
+impl RankWheel {
+    pub fn new(fitnesses: &[usize], pressure: f64) -> Self {
+        let mut total = 0.0;
+        let cumulative = rank_probabilities(fitnesses, pressure)
+            .into_iter()
+            .map(|p| {
+                total += p;
+                total
+            })
+            .collect();
+        RankWheel { cumulative }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        let target = rng.gen::<f64>() * self.cumulative.last().copied().unwrap_or(0.0);
+        // Rounding can leave the last running total just below the target
+        self.cumulative.partition_point(|&c| c <= target).min(self.cumulative.len() - 1)
+    }
+}
+
+/// Parent selection for one generation, set up once from its fitness scores.
+pub struct Selector<'a> {
+    population: &'a [SUBLEQProgram],
+    fitnesses: &'a [usize],
+    strategy: Strategy,
+}
+
+enum Strategy {
+    Tournament(usize),
+    Roulette(RouletteWheel),
+    Uniform,
+    Rank(RankWheel),
+}
+
+impl<'a> Selector<'a> {
+    pub fn new(config: &GaConfig, population: &'a [SUBLEQProgram], fitnesses: &'a [usize]) -> Self {
+        let strategy = match config.selection {
+            Selection::Tournament => Strategy::Tournament(config.tournament_size),
+            Selection::Roulette => Strategy::Roulette(RouletteWheel::new(fitnesses)),
+            Selection::Uniform => Strategy::Uniform,
+            Selection::Rank => Strategy::Rank(RankWheel::new(fitnesses, config.rank_pressure)),
+        };
+        Selector { population, fitnesses, strategy }
+    }
+
+    /// Score of the individual at `index`.
+    pub fn fitness(&self, index: usize) -> usize {
+        self.fitnesses[index]
+    }
+
+    /// Index of the next parent.
+    pub fn select(&self, rng: &mut impl Rng) -> usize {
+        match &self.strategy {
+            Strategy::Tournament(k) => tournament_select(self.population, self.fitnesses, *k, rng),
+            Strategy::Roulette(wheel) => wheel.sample(rng),
+            Strategy::Uniform => rng.gen_range(0..self.population.len()),
+            Strategy::Rank(wheel) => wheel.sample(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::testing::proptest_config;
+
+    proptest! {
+        #![proptest_config(proptest_config())]
+
+        #[test]
+        fn rank_probabilities_sum_to_one_in_fitness_order(fitnesses in prop::collection::vec(0..20usize, 1..50), pressure in 1.0..=2.0f64) {
+            let probabilities = rank_probabilities(&fitnesses, pressure);
+            prop_assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            for (i, j) in (0..fitnesses.len()).flat_map(|i| (0..fitnesses.len()).map(move |j| (i, j))) {
+                if fitnesses[i] == fitnesses[j] {
+                    prop_assert!((probabilities[i] - probabilities[j]).abs() < 1e-12);
+                } else if fitnesses[i] > fitnesses[j] {
+                    prop_assert!(probabilities[i] >= probabilities[j]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_pressure_never_picks_the_least_fit() {
+        let probabilities = rank_probabilities(&[30, 10, 20], 2.0);
+        assert_eq!(probabilities[1], 0.0);
+        assert!(probabilities[0] > probabilities[2] && probabilities[2] > 0.0);
+        assert_eq!(rank_probabilities(&[4, 4, 4], 2.0), vec![1.0 / 3.0; 3]);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::{HashMap, VecDeque};
+
+use rand::Rng;
+
+use crate::vm::address;
+use crate::{GaConfig, InstructionSet, SUBLEQProgram};
+
+/// Fraction of the arena the reaper keeps free for newborns.
+const REAP_FREE_FRACTION: f64 = 0.25;
+
+/// A program living in the soup.
+#[derive(Clone, Debug)]
+pub struct Organism {
+    /// First arena cell of the organism's code.
+    pub start: usize,
+    pub len: usize,
+    /// Program counter, relative to `start`.
+    pub pc: usize,
+    /// Round in which the organism was born.
+    pub born: usize,
+    pub offspring: usize,
+}
+
+/// Totals since the soup was created.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SoupStats {
+    pub births: usize,
+    pub deaths: usize,
+}
+
+/// A Tierra-style arena in which many programs run side by side and reproduce by
+/// copying themselves into free space.
+///
+/// Each organism sees a window of `memory_size` cells starting at its own code,
+/// wrapping around the arena, and is executed with the usual SUBLEQ rules, so a
+/// program evolved by [`run`](crate::run) behaves the same here except that its
+/// window holds its neighbours instead of zeros. Organisms take turns running up to
+/// `soup_slice` instructions. When an organism ends its turn with an exact copy of
+/// its own code right after itself, in cells no one owns, that copy is born as a new
+/// organism. Organisms die when they halt, and the oldest are reaped whenever free
+/// space drops below a quarter of the arena. Dead organisms' cells are freed but
+/// keep their contents.
+pub struct Soup {
+    memory: Vec<i32>,
+    /// Whether each cell belongs to a living organism.
+    owned: Vec<bool>,
+    free: usize,
+    /// Living organisms, oldest first.
+    organisms: VecDeque<Organism>,
+    memory_size: i32,
+    slice: usize,
+    isa: InstructionSet,
+    wrap_pc: bool,
+    protect_code: bool,
+    round: usize,
+    stats: SoupStats,
+}
+
+impl Soup {
+    /// Seeds the arena with `population` random programs (or `seed_copies` copies of
+    /// `seed_program` and random ones) spaced evenly across it.
+    pub fn new(config: &GaConfig, rng: &mut impl Rng) -> Self {
+        let mut soup = Soup {
+            memory: vec![0; config.soup_size],
+            owned: vec![false; config.soup_size],
+            free: config.soup_size,
+            organisms: VecDeque::new(),
+            memory_size: config.memory_size,
+            slice: config.soup_slice,
+            isa: config.isa,
+            wrap_pc: config.wrap_pc,
+            protect_code: config.protect_code,
+            round: 0,
+            stats: SoupStats::default(),
+        };
+        let spacing = config.soup_size / config.population;
+        let seed_slots = config.seed_slots();
+        for i in 0..config.population {
+            let program = match &config.seed_program {
+                Some(code) if seed_slots.contains(&i) => SUBLEQProgram::from_code(code.clone()),
+                _ => SUBLEQProgram::new(rng.gen_range(config.min_len..=config.max_len), config.memory_size, rng),
+            };
+            let start = i * spacing;
+            soup.memory[start..start + program.code().len()].copy_from_slice(program.code());
+            soup.spawn(start, program.code().len());
+        }
+        soup
+    }
+
+    pub fn memory(&self) -> &[i32] {
+        &self.memory
+    }
+
+    pub fn organisms(&self) -> impl Iterator<Item = &Organism> {
+        self.organisms.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.organisms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.organisms.is_empty()
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn stats(&self) -> SoupStats {
+        self.stats
+    }
+
+    /// Number of arena cells no organism owns.
+    pub fn free_cells(&self) -> usize {
+        self.free
+    }
+
+    /// The code of an organism as it currently stands in the arena.
+    pub fn genome(&self, organism: &Organism) -> Vec<i32> {
+        (0..organism.len).map(|i| self.memory[(organism.start + i) % self.memory.len()]).collect()
+    }
+
+    /// The most common genome among the living, with its number of carriers.
+    pub fn dominant_genome(&self) -> Option<(Vec<i32>, usize)> {
+        let mut counts: HashMap<Vec<i32>, usize> = HashMap::new();
+        for organism in &self.organisms {
+            *counts.entry(self.genome(organism)).or_default() += 1;
+        }
+        counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+    }
+
+    /// Gives every organism alive at the start of the round one turn, then reaps.
+    pub fn step_round(&mut self) {
+        for _ in 0..self.organisms.len() {
+            let Some(mut organism) = self.organisms.pop_front() else { break };
+            if self.run_turn(&mut organism) {
+                self.try_birth(&mut organism);
+                self.organisms.push_back(organism);
+            } else {
+                self.release(&organism);
+                self.stats.deaths += 1;
+            }
+        }
+        self.reap();
+        self.round += 1;
+    }
+
+    /// Runs one turn and returns whether the organism is still alive.
+    fn run_turn(&mut self, organism: &mut Organism) -> bool {
+        let arena = self.memory.len();
+        let cell = |offset: usize| (organism.start + offset) % arena;
+        for _ in 0..self.slice {
+            if self.fell_off(organism) {
+                return false;
+            }
+            let pc = organism.pc;
+            // The window wraps too, for an instruction fetched across its end
+            let window = self.memory_size as usize;
+            let a = address(self.memory[cell(pc)], self.memory_size);
+            let b = address(self.memory[cell((pc + 1) % window)], self.memory_size);
+            let c = address(self.memory[cell((pc + 2) % window)], self.memory_size);
+            let (value, branch) = self.isa.apply(self.memory[cell(a)], self.memory[cell(b)]);
+            // An organism's protected code is its own, at the start of its window
+            if !(self.protect_code && a < organism.len) {
+                self.memory[cell(a)] = value;
+            }
+            organism.pc = if branch { c } else { pc + 3 };
+            if self.wrap_pc {
+                organism.pc %= self.memory_size as usize;
+            }
+        }
+        !self.fell_off(organism)
+    }
+
+    fn fell_off(&self, organism: &Organism) -> bool {
+        !self.wrap_pc && organism.pc >= self.memory_size as usize - 2
+    }
+
+    fn try_birth(&mut self, parent: &mut Organism) {
+        let arena = self.memory.len();
+        let daughter = (parent.start + parent.len) % arena;
+        let free = (0..parent.len).all(|i| !self.owned[(daughter + i) % arena]);
+        let copied = (0..parent.len).all(|i| self.memory[(parent.start + i) % arena] == self.memory[(daughter + i) % arena]);
+        if free && copied {
+            self.spawn(daughter, parent.len);
+            parent.offspring += 1;
+            self.stats.births += 1;
+        }
+    }
+
+    fn spawn(&mut self, start: usize, len: usize) {
+        let arena = self.memory.len();
+        for i in 0..len {
+            self.owned[(start + i) % arena] = true;
+        }
+        self.free -= len;
+        self.organisms.push_back(Organism { start, len, pc: 0, born: self.round, offspring: 0 });
+    }
+
+    fn release(&mut self, organism: &Organism) {
+        let arena = self.memory.len();
+        for i in 0..organism.len {
+            self.owned[(organism.start + i) % arena] = false;
+        }
+        self.free += organism.len;
+    }
+
+    fn reap(&mut self) {
+        let target = (self.memory.len() as f64 * REAP_FREE_FRACTION) as usize;
+        while self.free < target {
+            let Some(oldest) = self.organisms.pop_front() else { break };
+            self.release(&oldest);
+            self.stats.deaths += 1;
+        }
+    }
+}
@@ -0,0 +1,441 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{canonicalize, FitnessCache, SUBLEQProgram, VmConfig};
+
+/// Sort key putting fitter programs first and breaking ties between equally fit ones by
+/// shorter code, then lexicographically smaller code, so the outcome never depends on
+/// where a program sits in the population.
+pub(crate) fn rank_key(program: &SUBLEQProgram, fitness: usize) -> (Reverse<usize>, usize, &[i32]) {
+    (Reverse(fitness), program.code().len(), program.code())
+}
+
+/// Index of the best program of a non-empty population under [`rank_key`].
+pub(crate) fn best_index(population: &[SUBLEQProgram], fitness_scores: &[usize]) -> usize {
+    (0..population.len()).min_by_key(|&i| rank_key(&population[i], fitness_scores[i])).unwrap()
+}
+
+/// Mean normalized Hamming distance between `samples` random pairs of distinct individuals.
+///
+/// Genomes are compared in [`canonicalize`]d form, position by position over the
+/// longer of the two, so each word past the end of the shorter one counts as a
+/// difference. The result ranges from 0 (identical genomes) to 1; populations of
+/// fewer than two score 0. Sampling keeps the cost at `O(samples * max_len)` rather
+/// than quadratic in the population size.
+pub fn diversity(population: &[SUBLEQProgram], samples: usize, vm: &VmConfig, rng: &mut impl Rng) -> f64 {
+    if population.len() < 2 || samples == 0 {
+        return 0.0;
+    }
+    let genomes: Vec<Vec<i32>> = population.iter().map(|p| canonicalize(p.code(), vm)).collect();
+    let total: f64 = (0..samples)
+        .map(|_| {
+            let i = rng.gen_range(0..population.len());
+            let j = (i + rng.gen_range(1..population.len())) % population.len();
+            hamming_distance(&genomes[i], &genomes[j])
+        })
+        .sum();
+    total / samples as f64
+}
+
+/// Number of equal-width ranges gene values are grouped into by [`population_entropy`].
+pub const ENTROPY_BUCKETS: usize = 32;
+
+/// Mean Shannon entropy of the genes at each position shared by every program, scaled
+/// to range from 0 (all programs agree) to 1.
+///
+/// Genes are grouped into [`ENTROPY_BUCKETS`] equal ranges of `-memory_size..memory_size`,
+/// the values random genes take, so nearby addresses count as the same gene; otherwise
+/// a population of a few hundred programs could never fill the `2 * memory_size`
+/// possible values and the entropy would mostly reflect the population size. Takes one
+/// pass over the population, so unlike [`diversity`] it needs no sampling. Returns 0
+/// for an empty population or one containing an empty program.
+pub fn population_entropy(population: &[SUBLEQProgram], memory_size: i32) -> f64 {
+    let positions = population.iter().map(|p| p.code().len()).min().unwrap_or(0);
+    if positions == 0 {
+        return 0.0;
+    }
+    let memory_size = memory_size as i64;
+    let mut counts = [0usize; ENTROPY_BUCKETS];
+    let mut total = 0.0;
+    for position in 0..positions {
+        counts.fill(0);
+        for program in population {
+            let gene = (program.code()[position] as i64).clamp(-memory_size, memory_size - 1);
+            counts[((gene + memory_size) * ENTROPY_BUCKETS as i64 / (2 * memory_size)) as usize] += 1;
+        }
+        total -= counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / population.len() as f64;
+                p * p.log2()
+            })
+            .sum::<f64>();
+    }
+    total / positions as f64 / (ENTROPY_BUCKETS as f64).log2()
+}
+
+/// Distribution of the genes at one position across a population; see [`gene_stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct GeneStats {
+    pub position: usize,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub stddev: f64,
+    /// The most common value, the smallest on ties.
+    pub mode: i32,
+    /// Fraction of the population holding `mode`: 1 where every program agrees, near 0
+    /// where the position is free to drift.
+    pub convergence: f64,
+}
+
+/// [`GeneStats`] of each position shared by every program of `population`, up to the
+/// shortest one's length.
+///
+/// Values are compared as written, so two genes addressing the same cell, such as -1
+/// and `memory_size - 1`, count as different. Empty for an empty population.
+pub fn gene_stats(population: &[SUBLEQProgram]) -> Vec<GeneStats> {
+    let positions = population.iter().map(|p| p.code().len()).min().unwrap_or(0);
+    let n = population.len() as f64;
+    (0..positions)
+        .map(|position| {
+            let genes = population.iter().map(|p| p.code()[position] as f64);
+            let mean = genes.clone().sum::<f64>() / n;
+            let stddev = (genes.map(|gene| (gene - mean).powi(2)).sum::<f64>() / n).sqrt();
+            let mut counts: HashMap<i32, usize> = HashMap::new();
+            for program in population {
+                *counts.entry(program.code()[position]).or_default() += 1;
+            }
+            let (mode, count) = counts.into_iter().max_by_key(|&(value, count)| (count, Reverse(value))).unwrap();
+            GeneStats { position, mean, stddev, mode, convergence: count as f64 / n }
+        })
+        .collect()
+}
+
+/// Writes `stats` as CSV with a header row, one position per row.
+pub fn save_gene_stats(stats: &[GeneStats], path: &Path) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "position,mean,stddev,mode,convergence")?;
+    for gene in stats {
+        writeln!(writer, "{},{},{},{},{}", gene.position, gene.mean, gene.stddev, gene.mode, gene.convergence)?;
+    }
+    writer.flush()
+}
+
+fn hamming_distance(a: &[i32], b: &[i32]) -> f64 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 0.0;
+    }
+    let mismatches = a.iter().zip(b).filter(|(x, y)| x != y).count() + a.len().abs_diff(b.len());
+    mismatches as f64 / len as f64
+}
+
+/// Levenshtein edit distance between two programs' code, each word one symbol: the
+/// fewest insertions, deletions and substitutions turning `a` into `b`.
+///
+/// Unlike the Hamming distance behind [`diversity`], a single inserted or deleted word
+/// costs 1 rather than shifting every word after it. Takes `O(a.len() * b.len())` time,
+/// which for genomes bounded by `max_len` stays small, and one row of working memory.
+pub fn program_distance(a: &SUBLEQProgram, b: &SUBLEQProgram) -> usize {
+    edit_distance(a.code(), b.code())
+}
+
+fn edit_distance(a: &[i32], b: &[i32]) -> usize {
+    edit_distance_within(a, b, usize::MAX).unwrap()
+}
+
+/// Edit distance between `a` and `b` if it is at most `limit`, giving up as soon as
+/// every alignment of a prefix of `a` costs more.
+pub(crate) fn edit_distance_within(a: &[i32], b: &[i32], limit: usize) -> Option<usize> {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+        if row.iter().all(|&cost| cost > limit) {
+            return None;
+        }
+    }
+    Some(row[b.len()]).filter(|&distance| distance <= limit)
+}
+
+/// Summary of one scored generation.
+#[derive(Clone, Debug)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best_fitness: usize,
+    pub mean_fitness: f64,
+    /// Population standard deviation of the fitness scores.
+    pub std_fitness: f64,
+    pub median_fitness: f64,
+    pub min_fitness: usize,
+    /// Number of distinct [`canonicalize`]d genomes in the population.
+    pub unique_genotypes: usize,
+    /// Sampled genetic diversity, as computed by [`diversity`].
+    pub diversity: f64,
+    /// Per-position gene entropy of the whole population, as computed by [`population_entropy`].
+    pub entropy: f64,
+    /// Mean program length in words.
+    pub mean_length: f64,
+    /// Length in words of each program, in population order.
+    pub lengths: Vec<usize>,
+    /// Mean of the programs' own mutation rates.
+    pub mean_mutation_rate: f64,
+    /// Code of the best program; equally fit programs are ranked shorter first, then by code.
+    pub best_genome: Vec<i32>,
+    /// Best fitness on each island, in order; just the best fitness without islands.
+    pub island_best: Vec<usize>,
+    /// Number of random immigrants that replaced offspring this generation.
+    pub immigrants: usize,
+    /// Number of this generation's children that underwent a block mutation.
+    pub block_mutations: usize,
+    /// True if the next generation is bred with raised mutation rates.
+    pub hypermutation: bool,
+    /// Global mutation rate set by `adaptive_mutation`, if enabled.
+    pub adaptive_rate: Option<f64>,
+    /// Temperature the next mutant is judged at, in anneal mode.
+    pub temperature: Option<f64>,
+    /// Number of worse mutants annealing has accepted so far, in anneal mode.
+    pub accepted_worse: Option<usize>,
+    /// Number of programs executed for this generation that meet the objective, with
+    /// `verify_all`.
+    pub verified: Option<usize>,
+    /// First generation so far in which a program wrote a full copy of itself anywhere,
+    /// as found by [`has_full_copy`](crate::has_full_copy), whether or not it counts as
+    /// replication.
+    pub first_full_copy: Option<usize>,
+}
+
+impl GenerationStats {
+    /// Summarizes a non-empty population run on `vm`, its fitness scores and its
+    /// measured diversity.
+    pub fn new(generation: usize, population: &[SUBLEQProgram], fitness_scores: &[usize], diversity: f64, vm: &VmConfig) -> Self {
+        let best = best_index(population, fitness_scores);
+        let best_fitness = fitness_scores[best];
+        let mut sorted = fitness_scores.to_vec();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        let median_fitness = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) as f64 / 2.0
+        } else {
+            sorted[mid] as f64
+        };
+        let mean_fitness = sorted.iter().sum::<usize>() as f64 / sorted.len() as f64;
+        let variance = sorted.iter().map(|&score| (score as f64 - mean_fitness).powi(2)).sum::<f64>() / sorted.len() as f64;
+        GenerationStats {
+            generation,
+            best_fitness,
+            mean_fitness,
+            std_fitness: variance.sqrt(),
+            median_fitness,
+            min_fitness: sorted[0],
+            unique_genotypes: population.iter().map(|p| canonicalize(p.code(), vm)).collect::<HashSet<_>>().len(),
+            diversity,
+            entropy: population_entropy(population, vm.memory_size),
+            mean_length: population.iter().map(|p| p.code().len()).sum::<usize>() as f64 / population.len() as f64,
+            lengths: population.iter().map(|p| p.code().len()).collect(),
+            mean_mutation_rate: population.iter().map(|p| p.mutation_rate()).sum::<f64>() / population.len() as f64,
+            best_genome: population[best].code().to_vec(),
+            island_best: vec![best_fitness],
+            immigrants: 0,
+            block_mutations: 0,
+            hypermutation: false,
+            adaptive_rate: None,
+            temperature: None,
+            accepted_worse: None,
+            verified: None,
+            first_full_copy: None,
+        }
+    }
+}
+
+/// Overview of a finished run, also written by `--summary-json`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummary {
+    /// Number of generations evaluated.
+    pub generations: usize,
+    pub elapsed_secs: f64,
+    pub best_fitness: usize,
+    /// Code of the fittest program seen; empty if no generation was evaluated.
+    pub best_genome: Vec<i32>,
+    /// Fitness statistics of the last generation evaluated, or zero if there was none.
+    pub final_mean_fitness: f64,
+    pub final_median_fitness: f64,
+    pub final_std_fitness: f64,
+    pub unique_genotypes: usize,
+    /// Fraction of fitness lookups answered by the cache, if it was enabled.
+    pub cache_hit_rate: Option<f64>,
+    /// First generation in which a program wrote a full copy of itself anywhere; see
+    /// [`GenerationStats::first_full_copy`].
+    pub first_full_copy: Option<usize>,
+    /// Number of worse mutants accepted, in anneal mode.
+    pub accepted_worse: Option<usize>,
+}
+
+impl RunSummary {
+    pub(crate) fn new(
+        generations: usize,
+        elapsed: Duration,
+        best: Option<&(SUBLEQProgram, usize)>,
+        last: Option<&GenerationStats>,
+        cache: Option<&FitnessCache>,
+    ) -> Self {
+        RunSummary {
+            generations,
+            elapsed_secs: elapsed.as_secs_f64(),
+            best_fitness: best.map_or(0, |(_, fitness)| *fitness),
+            best_genome: best.map_or_else(Vec::new, |(program, _)| program.code().to_vec()),
+            final_mean_fitness: last.map_or(0.0, |stats| stats.mean_fitness),
+            final_median_fitness: last.map_or(0.0, |stats| stats.median_fitness),
+            final_std_fitness: last.map_or(0.0, |stats| stats.std_fitness),
+            unique_genotypes: last.map_or(0, |stats| stats.unique_genotypes),
+            cache_hit_rate: cache.map(FitnessCache::hit_rate),
+            first_full_copy: last.and_then(|stats| stats.first_full_copy),
+            accepted_worse: last.and_then(|stats| stats.accepted_worse),
+        }
+    }
+}
+
+/// A CSV file receiving one [`GenerationStats`] row per generation.
+///
+/// Each row is flushed as soon as it is written, so the file can be followed during a run.
+pub(crate) struct CsvLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CsvLog {
+    /// Creates (or truncates) the file and writes the header.
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "generation,best_fitness,mean_fitness,median_fitness,min_fitness,unique_genotypes,diversity,entropy,mean_length,std_fitness,immigrants,block_mutations,hypermutation,first_full_copy,temperature,accepted_worse,verified")?;
+        writer.flush()?;
+        Ok(CsvLog { path: path.to_path_buf(), writer })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn write(&mut self, stats: &GenerationStats) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            stats.generation,
+            stats.best_fitness,
+            stats.mean_fitness,
+            stats.median_fitness,
+            stats.min_fitness,
+            stats.unique_genotypes,
+            stats.diversity,
+            stats.entropy,
+            stats.mean_length,
+            stats.std_fitness,
+            stats.immigrants,
+            stats.block_mutations,
+            stats.hypermutation as u8,
+            stats.first_full_copy.map_or_else(String::new, |generation| generation.to_string()),
+            stats.temperature.map_or_else(String::new, |temperature| temperature.to_string()),
+            stats.accepted_worse.map_or_else(String::new, |accepted| accepted.to_string()),
+            stats.verified.map_or_else(String::new, |verified| verified.to_string())
+        )?;
+        self.writer.flush()
+    }
+}
+
+/// One line of a [`JsonlLog`]: the CSV columns, plus the best genome and the per-island
+/// bests, which a CSV row cannot hold.
+#[derive(Serialize)]
+struct JsonlRecord<'a> {
+    generation: usize,
+    best_fitness: usize,
+    mean_fitness: f64,
+    median_fitness: f64,
+    min_fitness: usize,
+    std_fitness: f64,
+    unique_genotypes: usize,
+    diversity: f64,
+    entropy: f64,
+    mean_length: f64,
+    immigrants: usize,
+    block_mutations: usize,
+    hypermutation: bool,
+    first_full_copy: Option<usize>,
+    temperature: Option<f64>,
+    accepted_worse: Option<usize>,
+    verified: Option<usize>,
+    island_best: &'a [usize],
+    best_genome: &'a [i32],
+}
+
+/// A JSON Lines file receiving one object per generation, flushed like [`CsvLog`] rows.
+pub(crate) struct JsonlLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl JsonlLog {
+    /// Creates (or truncates) the file.
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        Ok(JsonlLog { path: path.to_path_buf(), writer: BufWriter::new(File::create(path)?) })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn write(&mut self, stats: &GenerationStats) -> io::Result<()> {
+        let record = JsonlRecord {
+            generation: stats.generation,
+            best_fitness: stats.best_fitness,
+            mean_fitness: stats.mean_fitness,
+            median_fitness: stats.median_fitness,
+            min_fitness: stats.min_fitness,
+            std_fitness: stats.std_fitness,
+            unique_genotypes: stats.unique_genotypes,
+            diversity: stats.diversity,
+            entropy: stats.entropy,
+            mean_length: stats.mean_length,
+            immigrants: stats.immigrants,
+            block_mutations: stats.block_mutations,
+            hypermutation: stats.hypermutation,
+            first_full_copy: stats.first_full_copy,
+            temperature: stats.temperature,
+            accepted_worse: stats.accepted_worse,
+            verified: stats.verified,
+            island_best: &stats.island_best,
+            best_genome: &stats.best_genome,
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_ties_by_length_then_code() {
+        let programs: Vec<SUBLEQProgram> =
+            [vec![5, 1, 2, 0, 0, 0], vec![4, 9, 9], vec![3, 0, 0], vec![0, 0, 1]].into_iter().map(SUBLEQProgram::from_code).collect();
+        let scores = [7, 7, 7, 6];
+        assert_eq!(best_index(&programs, &scores), 2, "the shorter, then smaller, of the fittest");
+        let reversed: Vec<SUBLEQProgram> = programs.iter().rev().map(|p| SUBLEQProgram::from_code(p.code().to_vec())).collect();
+        let reversed_scores: Vec<usize> = scores.iter().rev().copied().collect();
+        assert_eq!(reversed[best_index(&reversed, &reversed_scores)].code(), programs[2].code(), "the order of the population does not matter");
+    }
+}
@@ -0,0 +1,19 @@
+//! Shared helpers for the unit and property tests.
+
+use proptest::prelude::*;
+use proptest::test_runner::RngSeed;
+
+use crate::GaConfig;
+
+/// Property tests draw from a fixed seed, so CI runs the same cases every time.
+pub(crate) fn proptest_config() -> ProptestConfig {
+    ProptestConfig { rng_seed: RngSeed::Fixed(0x5eed), failure_persistence: None, ..ProptestConfig::default() }
+}
+
+/// Code of programs the default config allows: lengths in `[min_len, max_len]` and
+/// genes in `-memory_size..memory_size`. Code rather than programs, which have no
+/// `Debug` to report failing cases with.
+pub(crate) fn valid_code() -> impl Strategy<Value = Vec<i32>> {
+    let config = GaConfig::default();
+    prop::collection::vec(-config.memory_size..config.memory_size, config.min_len..=config.max_len)
+}
@@ -0,0 +1,129 @@
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use log::LevelFilter;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+use subleq_self_replicator::{disassemble, GenerationStats, InstructionSet};
+
+use crate::length_histogram;
+
+/// Minimum time between redraws, so fast runs are not slowed down by the terminal.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Width of each bar in the length histogram, including the gap after it.
+const BAR_SPACING: u16 = 5;
+
+/// Live dashboard fed one [`GenerationStats`] per generation by `--tui`.
+///
+/// The terminal is taken over on creation and restored when the dashboard is dropped.
+/// Logging is silenced in between, since it would draw over the dashboard.
+pub struct Dashboard {
+    terminal: DefaultTerminal,
+    log_level: LevelFilter,
+    memory_size: i32,
+    isa: InstructionSet,
+    best_fitness: Vec<u64>,
+    mean_fitness: Vec<u64>,
+    latest: Option<GenerationStats>,
+    drawn: Option<Instant>,
+}
+
+impl Dashboard {
+    pub fn new(memory_size: i32, isa: InstructionSet) -> Self {
+        let log_level = log::max_level();
+        log::set_max_level(LevelFilter::Off);
+        Dashboard {
+            terminal: ratatui::init(),
+            log_level,
+            memory_size,
+            isa,
+            best_fitness: Vec::new(),
+            mean_fitness: Vec::new(),
+            latest: None,
+            drawn: None,
+        }
+    }
+
+    /// Records a generation and redraws if due; breaks once `q` (or Ctrl-C) is pressed.
+    pub fn update(&mut self, stats: GenerationStats) -> ControlFlow<()> {
+        self.best_fitness.push(stats.best_fitness as u64);
+        self.mean_fitness.push(stats.mean_fitness.round() as u64);
+        self.latest = Some(stats);
+        if self.drawn.is_none_or(|drawn| drawn.elapsed() >= REDRAW_INTERVAL) {
+            let Dashboard { terminal, memory_size, isa, best_fitness, mean_fitness, latest, .. } = self;
+            terminal
+                .draw(|frame| draw(frame, *memory_size, *isa, best_fitness, mean_fitness, latest.as_ref().unwrap()))
+                .expect("cannot draw the dashboard");
+            self.drawn = Some(Instant::now());
+        }
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            if let Ok(Event::Key(key)) = event::read() {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.kind == KeyEventKind::Press && (key.code == KeyCode::Char('q') || ctrl_c) {
+                    return ControlFlow::Break(());
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        ratatui::restore();
+        log::set_max_level(self.log_level);
+    }
+}
+
+fn draw(frame: &mut Frame, memory_size: i32, isa: InstructionSet, best_fitness: &[u64], mean_fitness: &[u64], stats: &GenerationStats) {
+    let [top, bottom, help] = Layout::vertical([Constraint::Length(12), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [history, summary] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(top);
+    let [best_area, mean_area] = Layout::vertical([Constraint::Ratio(1, 2); 2]).areas(history);
+    let [lengths, genome] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(bottom);
+
+    frame.render_widget(sparkline(format!(" Best fitness: {} ", stats.best_fitness), best_fitness, best_area), best_area);
+    frame.render_widget(sparkline(format!(" Mean fitness: {:.2} ", stats.mean_fitness), mean_fitness, mean_area), mean_area);
+
+    let mut lines = vec![
+        format!("Generation:         {}", stats.generation),
+        format!("Median fitness:     {}", stats.median_fitness),
+        format!("Fitness std:        {:.2}", stats.std_fitness),
+        format!("Diversity:          {:.3}", stats.diversity),
+        format!("Distinct genotypes: {}", stats.unique_genotypes),
+        format!("Mean length:        {:.1}", stats.mean_length),
+        format!("Mean mutation rate: {:.4}", stats.mean_mutation_rate),
+        format!("Hypermutation:      {}", if stats.hypermutation { "on" } else { "off" }),
+    ];
+    if stats.island_best.len() > 1 {
+        lines.push(format!("Island best:        {:?}", stats.island_best));
+    }
+    frame.render_widget(Paragraph::new(lines.join("\n")).block(Block::bordered().title(" Population ")), summary);
+
+    let bars = length_bars(&stats.lengths, (lengths.width.saturating_sub(2) / BAR_SPACING).max(1) as usize);
+    let chart = BarChart::default()
+        .block(Block::bordered().title(" Program lengths "))
+        .data(BarGroup::new(bars))
+        .bar_width(BAR_SPACING - 1)
+        .bar_gap(1);
+    frame.render_widget(chart, lengths);
+
+    let listing = disassemble(&stats.best_genome, memory_size, isa);
+    let title = format!(" Best genome ({} words) ", stats.best_genome.len());
+    frame.render_widget(Paragraph::new(listing).block(Block::bordered().title(title)), genome);
+
+    frame.render_widget(Paragraph::new("q: stop and save the best program"), help);
+}
+
+/// Sparkline of the most recent values that fit in `area`.
+fn sparkline<'a>(title: String, values: &[u64], area: Rect) -> Sparkline<'a> {
+    let shown = values.len().min(area.width.saturating_sub(2) as usize);
+    Sparkline::default().block(Block::bordered().title(title)).data(values[values.len() - shown..].to_vec())
+}
+
+/// Bars of [`length_histogram`], labelled by their lower bound.
+fn length_bars<'a>(lengths: &[usize], max_bars: usize) -> Vec<Bar<'a>> {
+    length_histogram(lengths, max_bars).into_iter().map(|(from, count)| Bar::with_label(from.to_string(), count as u64)).collect()
+}
@@ -0,0 +1,536 @@
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::ops::DerefMut;
+
+use serde::Deserialize;
+
+use crate::Word;
+
+/// The one instruction the machine executes; every set decodes `(a, b, c)` and
+/// addresses memory the same way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstructionSet {
+    /// `mem[a] -= mem[b]`, then branch to `c` if the result is zero or negative.
+    #[default]
+    Subleq,
+    /// `mem[a] -= mem[b]`, then branch to `c` if the result is negative.
+    Subneg,
+    /// `mem[a] += mem[b]`, then branch to `c` if the result is zero or negative.
+    Addleq,
+}
+
+impl InstructionSet {
+    /// Every instruction set.
+    pub const ALL: [InstructionSet; 3] = [InstructionSet::Subleq, InstructionSet::Subneg, InstructionSet::Addleq];
+
+    /// The assembly name of the instruction.
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            InstructionSet::Subleq => "SUBLEQ",
+            InstructionSet::Subneg => "SUBNEG",
+            InstructionSet::Addleq => "ADDLEQ",
+        }
+    }
+
+    /// The value stored into `mem[a]` and whether the instruction branches.
+    pub(crate) fn apply<W: Word>(self, a: W, b: W) -> (W, bool) {
+        match self {
+            InstructionSet::Subleq => {
+                let value = a.wrapping_sub(b);
+                (value, value.is_non_positive())
+            }
+            InstructionSet::Subneg => {
+                let value = a.wrapping_sub(b);
+                (value, value.is_negative())
+            }
+            InstructionSet::Addleq => {
+                let value = a.wrapping_add(b);
+                (value, value.is_non_positive())
+            }
+        }
+    }
+}
+
+/// Machine parameters used when executing a program.
+#[derive(Clone, Debug)]
+pub struct VmConfig {
+    pub memory_size: i32,
+    pub max_steps: usize,
+    /// Stop when a taken branch has a negative raw target, as in canonical SUBLEQ,
+    /// instead of wrapping the target into memory.
+    pub halt_on_negative: bool,
+    /// Address of the memory-mapped I/O cell, or `None` for a machine without I/O.
+    ///
+    /// An instruction whose source operand `b` is the port stores the next input
+    /// value into `a` (halting once the input is exhausted); one whose destination
+    /// `a` is the port appends the value of `b` to the output instead of writing
+    /// memory. I/O instructions never branch. The last cell, `memory_size - 1`, is
+    /// the conventional choice since programs rarely load code there.
+    pub io_port: Option<usize>,
+    /// Values read through the I/O port, in order, wrapped into the program's word type.
+    pub input: Vec<i32>,
+    /// Stop as soon as the machine revisits an earlier state, since it would then loop
+    /// until `max_steps`. Detection is exact but may trail the loop's start by up to
+    /// twice its length.
+    pub detect_cycles: bool,
+    /// Instruction executed at every step.
+    pub isa: InstructionSet,
+    /// Advance the program counter modulo the memory size, so execution runs in a
+    /// circle instead of halting when it falls off the end.
+    pub wrap_pc: bool,
+    /// Send the program counter back to address 0 when it runs off the end of memory,
+    /// instead of halting, so a program only stops by halting explicitly or at
+    /// `max_steps`.
+    ///
+    /// Without `halt_on_negative` or an I/O port there is no explicit halt, so every
+    /// program runs to `max_steps` or, with `detect_cycles`, into a cycle. With
+    /// `wrap_pc` the program counter never runs off the end and this has no effect.
+    pub restart_at_end: bool,
+    /// Ignore writes into the cells the program was loaded into; instructions aimed
+    /// there still branch on the value they would have stored.
+    pub protect_code: bool,
+    /// Address the program is loaded at and starts executing from, wrapping around
+    /// the end of memory.
+    ///
+    /// The final memory in an [`ExecutionResult`] is rotated to begin at this address,
+    /// so fitness scans see the original at 0 and search all of memory whatever the
+    /// offset; [`Machine::memory`] and step records use real addresses.
+    pub load_offset: usize,
+    /// Values memory holds before the program is loaded over it, repeated from real
+    /// address 0 to fill it; empty for all zeros.
+    pub background: Vec<i32>,
+}
+
+/// Final state of one program execution on words of type `W`.
+///
+/// `memory` is owned unless the program was run in a caller's buffer with
+/// [`SUBLEQProgram::execute_into`](crate::SUBLEQProgram::execute_into).
+#[derive(Clone, Debug)]
+pub struct ExecutionResult<M = Vec<i32>, W = i32> {
+    pub memory: M,
+    pub steps: usize,
+    /// True if execution stopped on its own rather than by hitting `max_steps`.
+    pub halted: bool,
+    /// Number of memory writes, one per executed arithmetic instruction or input read
+    /// into memory; writes ignored by `protect_code` are not counted.
+    pub writes: usize,
+    /// True if the program rewrote at least one of the cells it was loaded into.
+    pub self_modified: bool,
+    /// Number of `writes` that landed in the program's own cells.
+    pub self_mod_writes: usize,
+    /// Values written to the I/O port, in order.
+    pub output: Vec<W>,
+    /// True if execution was cut short because the machine entered a cycle.
+    pub cycled: bool,
+}
+
+impl<W: Word> ExecutionResult<&mut [W], W> {
+    /// Copies the memory out of the borrowed buffer.
+    pub fn into_owned(self) -> ExecutionResult<Vec<W>, W> {
+        ExecutionResult {
+            memory: self.memory.to_vec(),
+            steps: self.steps,
+            halted: self.halted,
+            writes: self.writes,
+            self_modified: self.self_modified,
+            self_mod_writes: self.self_mod_writes,
+            output: self.output,
+            cycled: self.cycled,
+        }
+    }
+}
+
+/// Reduces an operand into a machine of `memory_size` cells.
+pub(crate) fn address<W: Word>(word: W, memory_size: i32) -> usize {
+    word.address(memory_size)
+}
+
+/// Loads `code` at `load_offset` in `memory`, which is cleared first, and runs it.
+pub(crate) fn execute<W: Word, M: DerefMut<Target = [W]>>(code: &[W], vm: &VmConfig, memory: M) -> ExecutionResult<M, W> {
+    let mut machine = Machine::with_memory(code, vm, memory);
+    while machine.step().is_some() {}
+    machine.finish()
+}
+
+/// Like [`execute`], but reports an execution that did not halt cleanly as an error.
+pub(crate) fn execute_checked<W: Word>(code: &[W], vm: &VmConfig) -> Result<ExecutionResult<Vec<W>, W>, ExecError<W>> {
+    let mut machine = Machine::new(code, vm);
+    while machine.step().is_some() {}
+    let fell_off = machine.fell_off();
+    let result = machine.finish();
+    if result.cycled {
+        Err(ExecError::Cycle(result))
+    } else if !result.halted {
+        Err(ExecError::StepLimit(result))
+    } else if result.writes == 0 {
+        Err(ExecError::NoWrites(result))
+    } else if fell_off {
+        Err(ExecError::FellOff(result))
+    } else {
+        Ok(result)
+    }
+}
+
+/// How an execution checked by [`SUBLEQProgram::execute_checked`](crate::SUBLEQProgram::execute_checked)
+/// ended abnormally; each variant carries the final state.
+///
+/// Variants are tried in declaration order, so a program that falls off the end
+/// without writing anything reports `NoWrites`.
+#[derive(Debug)]
+pub enum ExecError<W = i32> {
+    /// Execution was cut short by `detect_cycles`.
+    Cycle(ExecutionResult<Vec<W>, W>),
+    /// `max_steps` instructions ran without the program halting.
+    StepLimit(ExecutionResult<Vec<W>, W>),
+    /// The program halted without writing memory.
+    NoWrites(ExecutionResult<Vec<W>, W>),
+    /// Execution ran past the last instruction in memory instead of halting through a
+    /// negative branch target or exhausted input.
+    FellOff(ExecutionResult<Vec<W>, W>),
+}
+
+impl<W> ExecError<W> {
+    /// Final state of the execution.
+    pub fn result(&self) -> &ExecutionResult<Vec<W>, W> {
+        match self {
+            ExecError::Cycle(result) | ExecError::StepLimit(result) | ExecError::NoWrites(result) | ExecError::FellOff(result) => result,
+        }
+    }
+}
+
+impl<W> fmt::Display for ExecError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let steps = self.result().steps;
+        match self {
+            ExecError::Cycle(_) => write!(f, "program entered a cycle after {} steps", steps),
+            ExecError::StepLimit(_) => write!(f, "program did not halt within {} steps", steps),
+            ExecError::NoWrites(_) => write!(f, "program halted after {} steps without writing memory", steps),
+            ExecError::FellOff(_) => write!(f, "program ran off the end of memory after {} steps", steps),
+        }
+    }
+}
+
+impl<W: Debug> Error for ExecError<W> {}
+
+/// One executed instruction, as seen by [`Machine::step`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepRecord<W = i32> {
+    /// Address of the instruction.
+    pub pc: usize,
+    /// Operands after reduction into the address space.
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+    /// Value stored into `memory[a]`, if the instruction wrote memory.
+    pub written: Option<W>,
+    /// Value sent to the I/O port, if any.
+    pub output: Option<W>,
+    /// Address of the next instruction; meaningless once the machine has stopped.
+    pub next_pc: usize,
+}
+
+/// A SUBLEQ machine that can be advanced one instruction at a time.
+///
+/// [`SUBLEQProgram::execute`](crate::SUBLEQProgram::execute) is this machine run to
+/// completion, so a trace collected from [`step`](Self::step) matches it exactly.
+pub struct Machine<'a, M = Vec<i32>, W = i32> {
+    vm: &'a VmConfig,
+    memory: M,
+    pc: usize,
+    steps: usize,
+    writes: usize,
+    self_mod_writes: usize,
+    inputs_read: usize,
+    output: Vec<W>,
+    halted: bool,
+    cycled: bool,
+    cycles: Option<CycleDetector<W>>,
+    code_len: usize,
+}
+
+impl<'a, W: Word> Machine<'a, Vec<W>, W> {
+    /// Loads `code` at `load_offset` in a new memory holding the `background`.
+    pub fn new(code: &[W], vm: &'a VmConfig) -> Self {
+        Machine::with_memory(code, vm, vec![W::default(); vm.memory_size as usize])
+    }
+}
+
+impl<'a, W: Word, M: DerefMut<Target = [W]>> Machine<'a, M, W> {
+    /// Loads `code` at `load_offset` in `memory`, reusing its allocation instead of
+    /// creating one. The buffer is reset to the `background` first and must hold
+    /// `memory_size` cells.
+    ///
+    /// Panics if `code` is longer than memory; [`GaConfig::validate`](crate::GaConfig::validate)
+    /// rules this out for evolved programs.
+    pub fn with_memory(code: &[W], vm: &'a VmConfig, mut memory: M) -> Self {
+        assert_eq!(memory.len(), vm.memory_size as usize, "memory buffer must hold memory_size cells");
+        assert!(code.len() <= memory.len(), "program of {} words does not fit in {} cells of memory", code.len(), memory.len());
+        if vm.background.is_empty() {
+            memory.fill(W::default());
+        } else {
+            for (cell, &value) in memory.iter_mut().zip(vm.background.iter().cycle()) {
+                *cell = W::from_i32(value);
+            }
+        }
+        let offset = vm.load_offset % memory.len();
+        let (head, tail) = code.split_at(code.len().min(memory.len() - offset));
+        memory[offset..offset + head.len()].copy_from_slice(head);
+        memory[..tail.len()].copy_from_slice(tail);
+        let cycles = vm.detect_cycles.then(|| CycleDetector::new(&memory));
+        Machine {
+            vm,
+            memory,
+            pc: offset,
+            steps: 0,
+            writes: 0,
+            self_mod_writes: 0,
+            inputs_read: 0,
+            output: Vec::new(),
+            halted: false,
+            cycled: false,
+            cycles,
+            code_len: code.len(),
+        }
+    }
+
+    pub fn memory(&self) -> &[W] {
+        &self.memory
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// True once the machine has halted, hit the step cap, or detected a cycle.
+    pub fn stopped(&self) -> bool {
+        self.halted || self.cycled || self.fell_off() || self.steps >= self.vm.max_steps
+    }
+
+    fn fell_off(&self) -> bool {
+        !self.vm.wrap_pc && !self.vm.restart_at_end && self.pc >= self.memory.len() - 2
+    }
+
+    /// Executes one instruction, or returns `None` if the machine has already stopped.
+    pub fn step(&mut self) -> Option<StepRecord<W>> {
+        if self.stopped() {
+            return None;
+        }
+        let pc = self.pc;
+        if let Some(cycles) = &mut self.cycles {
+            if cycles.revisited(pc, self.inputs_read, &self.memory) {
+                self.cycled = true;
+                return None;
+            }
+        }
+
+        let memory_size = self.vm.memory_size;
+        let len = self.memory.len();
+        // Only a wrapping program counter can fetch an instruction across the end
+        let (b_at, c_at) = if pc + 2 < len { (pc + 1, pc + 2) } else { ((pc + 1) % len, (pc + 2) % len) };
+        let a = address(self.memory[pc], memory_size);
+        let b = address(self.memory[b_at], memory_size);
+        let raw_c = self.memory[c_at];
+        let c = address(raw_c, memory_size);
+        let next_pc = if self.vm.wrap_pc { (pc + 3) % len } else { pc + 3 };
+        let mut record = StepRecord { pc, a, b, c, written: None, output: None, next_pc };
+        self.steps += 1;
+
+        if let Some(port) = self.vm.io_port.filter(|&port| a == port || b == port) {
+            let value = if b == port {
+                match self.vm.input.get(self.inputs_read) {
+                    Some(&value) => {
+                        self.inputs_read += 1;
+                        W::from_i32(value)
+                    }
+                    None => {
+                        self.halted = true;
+                        return Some(record);
+                    }
+                }
+            } else {
+                self.memory[b]
+            };
+            if a == port {
+                self.output.push(value);
+                record.output = Some(value);
+            } else {
+                record.written = self.write(a, value);
+            }
+        } else if a < self.memory.len() && b < self.memory.len() {
+            let (value, branch) = self.vm.isa.apply(self.memory[a], self.memory[b]);
+            record.written = self.write(a, value);
+            if branch {
+                if self.vm.halt_on_negative && raw_c.is_negative() {
+                    self.halted = true;
+                    return Some(record);
+                }
+                record.next_pc = c % self.memory.len();
+            }
+        }
+        if self.vm.restart_at_end && !self.vm.wrap_pc && record.next_pc >= self.memory.len() - 2 {
+            record.next_pc = 0;
+        }
+        self.pc = record.next_pc;
+        Some(record)
+    }
+
+    /// True if `addr` is one of the cells the program was loaded into.
+    fn in_code(&self, addr: usize) -> bool {
+        let len = self.memory.len();
+        (addr + len - self.vm.load_offset % len) % len < self.code_len
+    }
+
+    /// Stores `value` unless the cell is protected, returning it if it was stored.
+    fn write(&mut self, addr: usize, value: W) -> Option<W> {
+        if self.vm.protect_code && self.in_code(addr) {
+            return None;
+        }
+        if let Some(cycles) = &mut self.cycles {
+            cycles.record_write(addr, self.memory[addr], value);
+        }
+        self.memory[addr] = value;
+        self.writes += 1;
+        if self.in_code(addr) {
+            self.self_mod_writes += 1;
+        }
+        Some(value)
+    }
+
+    pub fn finish(mut self) -> ExecutionResult<M, W> {
+        let halted = self.halted || self.fell_off();
+        let offset = self.vm.load_offset % self.memory.len();
+        self.memory.rotate_left(offset);
+        ExecutionResult {
+            memory: self.memory,
+            steps: self.steps,
+            halted,
+            writes: self.writes,
+            self_modified: self.self_mod_writes > 0,
+            self_mod_writes: self.self_mod_writes,
+            output: self.output,
+            cycled: self.cycled,
+        }
+    }
+}
+
+impl<W: Word, M: DerefMut<Target = [W]>> Iterator for Machine<'_, M, W> {
+    type Item = StepRecord<W>;
+
+    fn next(&mut self) -> Option<StepRecord<W>> {
+        self.step()
+    }
+}
+
+/// Brent's cycle detection over the full machine state.
+///
+/// A snapshot is retaken whenever the number of steps since the last one reaches
+/// the next power of two; a cycle exists once the current state equals the
+/// snapshot. An incremental hash of memory rules out most mismatches without
+/// comparing every cell.
+struct CycleDetector<W> {
+    hash: u64,
+    snapshot: (usize, usize, u64),
+    snapshot_memory: Vec<W>,
+    power: usize,
+    since_snapshot: usize,
+}
+
+impl<W: Word> CycleDetector<W> {
+    fn new(memory: &[W]) -> Self {
+        let hash = memory.iter().enumerate().fold(0, |hash, (addr, &value)| hash ^ cell_hash(addr, value));
+        CycleDetector { hash, snapshot: (usize::MAX, 0, 0), snapshot_memory: Vec::new(), power: 1, since_snapshot: 0 }
+    }
+
+    fn record_write(&mut self, addr: usize, old: W, new: W) {
+        self.hash ^= cell_hash(addr, old) ^ cell_hash(addr, new);
+    }
+
+    /// Checks the state about to execute against the snapshot, then advances the schedule.
+    fn revisited(&mut self, pc: usize, inputs_read: usize, memory: &[W]) -> bool {
+        let state = (pc, inputs_read, self.hash);
+        if state == self.snapshot && memory == self.snapshot_memory {
+            return true;
+        }
+        self.since_snapshot += 1;
+        if self.since_snapshot == self.power {
+            self.snapshot = state;
+            self.snapshot_memory.clear();
+            self.snapshot_memory.extend_from_slice(memory);
+            self.power *= 2;
+            self.since_snapshot = 0;
+        }
+        false
+    }
+}
+
+// SplitMix64 finalizer over the (address, value) pair
+fn cell_hash<W: Word>(addr: usize, value: W) -> u64 {
+    let mut z = ((addr as u64) << 32 ^ value.to_bits()).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GaConfig, SUBLEQProgram};
+
+    fn vm(memory_size: i32) -> VmConfig {
+        GaConfig { memory_size, max_steps: 50, ..GaConfig::default() }.vm()
+    }
+
+    #[test]
+    fn echoes_input_through_the_io_port() {
+        let vm = VmConfig { io_port: Some(15), input: vec![4, -2, 7], ..vm(16) };
+        // Read into cell 9, write cell 9 to the port, clear cell 12 to jump back to 0
+        let program = SUBLEQProgram::from_code(vec![9, 15, 3, 15, 9, 6, 12, 12, 0]);
+        let result = program.execute(&vm);
+        assert_eq!(result.output, vec![4, -2, 7]);
+        assert!(result.halted, "exhausted input halts");
+    }
+
+    #[test]
+    fn wrapping_pc_runs_past_the_end_of_memory() {
+        // Two instructions that never branch leave the program counter at 6, too near the
+        // end of an 8-cell memory for another instruction
+        let program = SUBLEQProgram::from_code(vec![6, 7, 0, 6, 7, 0, 5, 0]);
+        let fell_off = program.execute(&vm(8));
+        assert!(fell_off.halted);
+        assert_eq!(fell_off.steps, 2);
+        let wrapped = program.execute(&VmConfig { wrap_pc: true, ..vm(8) });
+        assert!(!wrapped.halted);
+        assert_eq!(wrapped.steps, 50);
+    }
+
+    /// Subtracts cell 1 from the program's own cell 0, jumping to 3, then from data cell 6,
+    /// jumping off the end.
+    fn self_and_data_writer() -> SUBLEQProgram {
+        SUBLEQProgram::from_code(vec![0, 1, 3, 6, 1, 6])
+    }
+
+    #[test]
+    fn protect_code_ignores_writes_into_the_program() {
+        let result = self_and_data_writer().execute(&VmConfig { protect_code: true, ..vm(8) });
+        assert_eq!(result.memory[0], 0, "the code is unchanged");
+        assert_eq!(result.memory[6], -1, "data is still written");
+        assert_eq!(result.steps, 2, "the ignored write still branched");
+        assert_eq!(result.writes, 1);
+    }
+
+    #[test]
+    fn counts_every_write() {
+        let result = self_and_data_writer().execute(&vm(8));
+        assert_eq!(result.steps, 2);
+        assert_eq!(result.writes, 2);
+    }
+
+    #[test]
+    fn reports_writes_into_its_own_code() {
+        let result = self_and_data_writer().execute(&vm(8));
+        assert_eq!(result.memory[0], -1, "the first instruction was overwritten");
+        assert!(result.self_modified);
+        assert_eq!(result.self_mod_writes, 1);
+    }
+}
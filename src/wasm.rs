@@ -0,0 +1,47 @@
+use std::ops::ControlFlow;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{run, run_hill_climb, GaConfig, Mode, ReplicationFitness, StopReason};
+
+/// What [`run_once`] hands back to JavaScript.
+#[derive(Serialize)]
+struct RunOutcome {
+    best_genome: Vec<i32>,
+    best_fitness: usize,
+    generations: usize,
+    replicated: bool,
+}
+
+/// Evolves self-replicators from JavaScript and returns
+/// `{ best_genome, best_fitness, generations, replicated }`.
+///
+/// `params` takes the keys of a TOML experiment file, all optional, and `seed`
+/// overrides its seed. The run ends after `generations` (or `max_time`); the defaults
+/// are sized for native runs, so a browser demo should pass a much smaller
+/// `population` and `generations`. Soup and coevolution modes and file outputs are not
+/// supported.
+#[wasm_bindgen]
+pub fn run_once(seed: u64, params: JsValue) -> Result<JsValue, JsError> {
+    let config: GaConfig = if params.is_undefined() || params.is_null() {
+        GaConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(params)?
+    };
+    let config = config.into_builder().seed(seed).build()?;
+    let fitness = Box::new(ReplicationFitness::new(config.fitness).with_parsimony(config.parsimony));
+    let report = match config.mode {
+        Mode::Soup => return Err(JsError::new("run_once does not support soup mode")),
+        Mode::Coevolution => return Err(JsError::new("run_once does not support coevolution mode")),
+        Mode::Hillclimb | Mode::Anneal => run_hill_climb(config, fitness, |_| ControlFlow::Continue(()))?,
+        Mode::Generational | Mode::SteadyState => run(config, fitness)?,
+    };
+    let outcome = RunOutcome {
+        best_genome: report.summary.best_genome,
+        best_fitness: report.best_fitness,
+        generations: report.summary.generations,
+        replicated: report.stop == StopReason::Replicated,
+    };
+    Ok(serde_wasm_bindgen::to_value(&outcome)?)
+}
@@ -0,0 +1,81 @@
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+use rand::distributions::uniform::SampleUniform;
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Integer type of one machine word, so programs and the machine can be built on
+/// `i16`, `i32` (the default everywhere) or `i64`.
+///
+/// Memory sizes, `VmConfig::input` and the GA stay in `i32`; inputs are wrapped into
+/// the word type as they are read.
+pub trait Word: Copy + Default + Eq + Ord + Hash + Debug + Display + Send + Sync + SampleUniform + Serialize + DeserializeOwned + 'static {
+    /// `value` wrapped into the word type.
+    fn from_i32(value: i32) -> Self;
+
+    fn wrapping_sub(self, other: Self) -> Self;
+
+    fn wrapping_add(self, other: Self) -> Self;
+
+    /// The word as an operand of a machine with `memory_size` cells, reduced with
+    /// `rem_euclid` so negative words count back from the end.
+    fn address(self, memory_size: i32) -> usize;
+
+    fn is_negative(self) -> bool;
+
+    /// The SUBLEQ branch test.
+    fn is_non_positive(self) -> bool;
+
+    /// A uniform random gene in `-memory_size..memory_size`, narrowed to the type's
+    /// range if it cannot hold `memory_size`.
+    fn random(memory_size: i32, rng: &mut impl Rng) -> Self;
+
+    /// The word's bits, zero-extended, for hashing machine states.
+    fn to_bits(self) -> u64;
+}
+
+macro_rules! impl_word {
+    ($word:ty, $unsigned:ty, $wide:ty) => {
+        impl Word for $word {
+            fn from_i32(value: i32) -> Self {
+                value as $word
+            }
+
+            fn wrapping_sub(self, other: Self) -> Self {
+                <$word>::wrapping_sub(self, other)
+            }
+
+            fn wrapping_add(self, other: Self) -> Self {
+                <$word>::wrapping_add(self, other)
+            }
+
+            fn address(self, memory_size: i32) -> usize {
+                (self as $wide).rem_euclid(memory_size as $wide) as usize
+            }
+
+            fn is_negative(self) -> bool {
+                self < 0
+            }
+
+            fn is_non_positive(self) -> bool {
+                self <= 0
+            }
+
+            fn random(memory_size: i32, rng: &mut impl Rng) -> Self {
+                let bound = (memory_size as i64).min(<$word>::MAX as i64) as $word;
+                rng.gen_range(-bound..bound)
+            }
+
+            fn to_bits(self) -> u64 {
+                self as $unsigned as u64
+            }
+        }
+    };
+}
+
+// Addresses are reduced in at least i32, the type of memory sizes
+impl_word!(i16, u16, i32);
+impl_word!(i32, u32, i32);
+impl_word!(i64, u64, i64);